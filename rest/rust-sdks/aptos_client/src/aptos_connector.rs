@@ -1,75 +1,58 @@
 use crate::errors::ConnectorError;
 use aptos::common::utils::{chain_id, get_sequence_number};
-use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
+use aptos_sdk::crypto::ed25519::{Ed25519PrivateKey, Ed25519Signature};
+use aptos_sdk::crypto::PrivateKey;
 use aptos_sdk::move_types::account_address::AccountAddress;
 use aptos_sdk::move_types::identifier::Identifier;
 use aptos_sdk::move_types::language_storage::ModuleId;
+use aptos_sdk::rest_client::aptos_api_types::{Transaction, UserTransaction};
+use aptos_sdk::rest_client::error::RestError;
 use aptos_sdk::rest_client::Client;
 use aptos_sdk::transaction_builder::TransactionFactory;
 use aptos_sdk::types::LocalAccount;
 use aptos_types::transaction::{EntryFunction, SignedTransaction, TransactionPayload};
+use aptos_types::vm_status::StatusCode;
 use ed25519_dalek::{PublicKey, SecretKey};
+use sequence_manager::SequenceManager;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{fmt::Display, future::Future};
 
-use crate::types::PullResponseAptos;
+use crate::types::{AptosSimulation, PullResponseAptos};
 use tiny_keccak::{Hasher, Sha3};
 
 const MODULE: &str = "<CONTRACT MODULE>"; // Module name of your contract. Ex. pull_example
 const ENTRY: &str = "<CONTRACT FUNCTION>"; // Module function name of your contract. Ex. get_pair_price
+const SIMULATION_GAS_MARGIN_PERCENT: u64 = 50; // Headroom added on top of the simulated gas usage
+const MAX_SEQUENCE_RESYNCS: usize = 3; // Resubmissions allowed after the node rejects a sequence number
 
 pub async fn invoke_aptos_chain(payload: PullResponseAptos, aptos_connector: AptosConnector) {
-    let account = Account::from_secret_key(aptos_connector.secret_key).unwrap();
-
-    let address = AccountAddress::from_hex_literal(&aptos_connector.sc_addr).unwrap();
-    let bytes = hex::decode(&payload.proof_bytes).unwrap();
-    let aptos_arg = TransactionPayload::EntryFunction(EntryFunction::new(
-        ModuleId::new(address, Identifier::new(MODULE).unwrap()),
-        Identifier::new(ENTRY).unwrap(),
-        vec![],
-        vec![
-            bcs::to_bytes(
-                &AccountAddress::from_hex_literal(&payload.oracle_holder_object).unwrap(),
-            )
-            .unwrap(),
-            bcs::to_bytes(&bytes).unwrap(),
-        ],
-    ));
-
-    let sequence_number = aptos_connector
-        .client
-        .with_aptos(|aptos| account.get_seq_num(aptos))
-        .await
-        .unwrap();
-    let tx = aptos_connector
-        .client
-        .with_aptos(|aptos| {
-            account.setup_transaction(
-                &aptos_arg,
-                aptos,
-                sequence_number,
-                aptos_connector.gas_budget,
-            )
-        })
-        .await
-        .unwrap();
-
-    let response = aptos_connector
-        .client
-        .with_aptos(|aptos| aptos.submit_and_wait(&tx))
-        .await
-        .unwrap()
-        .into_inner();
+    let response = aptos_connector.submit_proof(&payload).await.unwrap();
 
     println!("{:?}", response.transaction_info().unwrap().hash);
 }
 
+/// Strategy used to pick the gas unit price of a transaction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GasPricePolicy {
+    /// Always use the given gas unit price
+    Fixed(u64),
+    /// Use the node's deprioritized estimate
+    Low,
+    /// Use the node's regular estimate
+    #[default]
+    Market,
+    /// Use the node's prioritized estimate
+    Prioritized,
+}
+
 pub struct AptosConfig<'a> {
     secret_key: &'a str,
     client_url: &'a str,
     sc_address: &'a str,
     gas_budget: u64,
+    gas_price_policy: GasPricePolicy,
+    simulate_gas: bool,
 }
 
 impl<'a> AptosConfig<'a> {
@@ -84,8 +67,22 @@ impl<'a> AptosConfig<'a> {
             client_url,
             sc_address,
             gas_budget,
+            gas_price_policy: GasPricePolicy::default(),
+            simulate_gas: false,
         }
     }
+
+    /// Set the policy used to pick the gas unit price
+    pub fn with_gas_price_policy(mut self, gas_price_policy: GasPricePolicy) -> Self {
+        self.gas_price_policy = gas_price_policy;
+        self
+    }
+
+    /// Size `max_gas_amount` by simulating the transaction, `gas_budget` is then used as a cap
+    pub fn with_gas_simulation(mut self, simulate_gas: bool) -> Self {
+        self.simulate_gas = simulate_gas;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -94,6 +91,9 @@ pub struct AptosConnector {
     client: ClientWrapper,
     sc_addr: String,
     gas_budget: u64,
+    gas_price_policy: GasPricePolicy,
+    simulate_gas: bool,
+    sequence_manager: SequenceManager,
 }
 
 impl AptosConnector {
@@ -109,8 +109,248 @@ impl AptosConnector {
             secret_key: conf.secret_key.to_string(),
             sc_addr: conf.sc_address.to_string(),
             gas_budget: conf.gas_budget,
+            gas_price_policy: conf.gas_price_policy,
+            simulate_gas: conf.simulate_gas,
+            sequence_manager: SequenceManager::new(),
         })
     }
+
+    /// Submit the proof verification and wait for the transaction to be committed.
+    /// Sequence numbers are handed out locally, clones of the connector can submit concurrently.
+    /// The local counter is resynced from chain whenever it may have drifted: the node rejected a
+    /// sequence number, the submission outcome is unknown, or the transaction was not committed.
+    pub async fn submit_proof(
+        &self,
+        payload: &PullResponseAptos,
+    ) -> Result<Transaction, ConnectorError> {
+        let account = Account::from_secret_key(self.secret_key.clone())?;
+        let aptos_arg = self.proof_payload(payload)?;
+        let gas_unit_price = self.gas_unit_price().await?;
+        let max_gas_amount = self
+            .max_gas_amount(&account, &aptos_arg, gas_unit_price)
+            .await?;
+
+        let mut resyncs = 0;
+        let tx = loop {
+            let sequence_number = self
+                .sequence_manager
+                .reserve(|| self.client.with_aptos(|aptos| account.get_seq_num(aptos)))
+                .await?;
+            let tx = match self
+                .client
+                .with_aptos(|aptos| {
+                    account.setup_transaction(
+                        &aptos_arg,
+                        aptos,
+                        sequence_number,
+                        gas_unit_price,
+                        max_gas_amount,
+                    )
+                })
+                .await
+            {
+                Ok(tx) => tx,
+                Err(e) => {
+                    // Nothing was sent, the number can be handed out again
+                    self.sequence_manager.release(sequence_number).await;
+                    return Err(e);
+                }
+            };
+            match self
+                .client
+                .with_aptos(|aptos| aptos.submit(&tx))
+                .await
+                .map_err(ConnectorError::from)
+            {
+                Ok(_) => break tx,
+                Err(e) if is_sequence_number_rejection(&e) && resyncs < MAX_SEQUENCE_RESYNCS => {
+                    log::warn!("aptos: sequence number {sequence_number} rejected, resyncing: {e}");
+                    self.sequence_manager.reset().await;
+                    resyncs += 1;
+                }
+                Err(e) if is_rejection(&e) => {
+                    // The node answered with an error, the transaction is not in the mempool
+                    self.sequence_manager.release(sequence_number).await;
+                    return Err(e);
+                }
+                Err(e) => {
+                    // The transaction may have been accepted, the number can not be reused
+                    self.sequence_manager.reset().await;
+                    return Err(e);
+                }
+            }
+        };
+        match self
+            .client
+            .with_aptos(|aptos| aptos.wait_for_signed_transaction(&tx))
+            .await
+        {
+            Ok(response) => Ok(response.into_inner()),
+            Err(e) => {
+                // Expired or dropped transactions leave a gap later sequence numbers would wait on
+                log::warn!("aptos: transaction not committed, resyncing sequence number: {e}");
+                self.sequence_manager.reset().await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Gas unit price to use for the next transaction, according to the configured policy
+    pub async fn gas_unit_price(&self) -> Result<u64, ConnectorError> {
+        if let GasPricePolicy::Fixed(price) = self.gas_price_policy {
+            return Ok(price);
+        }
+        let estimation = self
+            .client
+            .with_aptos(|aptos| aptos.estimate_gas_price())
+            .await?
+            .into_inner();
+        let price = match self.gas_price_policy {
+            GasPricePolicy::Low => estimation
+                .deprioritized_gas_estimate
+                .unwrap_or(estimation.gas_estimate),
+            GasPricePolicy::Prioritized => estimation
+                .prioritized_gas_estimate
+                .unwrap_or(estimation.gas_estimate),
+            _ => estimation.gas_estimate,
+        };
+        log::debug!("aptos: estimated gas unit price {price}");
+        Ok(price)
+    }
+
+    /// Max gas amount to use for `payload`.
+    /// Without gas simulation this is the configured gas budget, otherwise the simulated gas usage
+    /// plus a safety margin, capped by the gas budget.
+    /// The simulation uses the on-chain sequence number, a locally reserved one may be ahead of it
+    /// while other transactions are in flight.
+    pub async fn max_gas_amount(
+        &self,
+        account: &Account,
+        payload: &TransactionPayload,
+        gas_unit_price: u64,
+    ) -> Result<u64, ConnectorError> {
+        if !self.simulate_gas {
+            return Ok(self.gas_budget);
+        }
+        let sequence_number = self
+            .client
+            .with_aptos(|aptos| account.get_seq_num(aptos))
+            .await?;
+        let simulation = self
+            .simulate(
+                account,
+                payload,
+                sequence_number,
+                gas_unit_price,
+                self.gas_budget,
+            )
+            .await?;
+        let info = &simulation.info;
+        if !info.success {
+            return Err(ConnectorError::Simulation(info.vm_status.clone()));
+        }
+        let gas_used = info.gas_used.0;
+        let max_gas_amount = gas_used + gas_used * SIMULATION_GAS_MARGIN_PERCENT / 100;
+        log::debug!("aptos: simulated gas used {gas_used}, max gas amount {max_gas_amount}");
+        Ok(max_gas_amount.min(self.gas_budget))
+    }
+
+    /// Entry function payload verifying the given proof
+    pub fn proof_payload(
+        &self,
+        payload: &PullResponseAptos,
+    ) -> Result<TransactionPayload, ConnectorError> {
+        let address = AccountAddress::from_hex_literal(&self.sc_addr)?;
+        let oracle_holder = AccountAddress::from_hex_literal(&payload.oracle_holder_object)?;
+        let bytes = hex::decode(&payload.proof_bytes)?;
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(address, identifier(MODULE)?),
+            identifier(ENTRY)?,
+            vec![],
+            vec![bcs::to_bytes(&oracle_holder)?, bcs::to_bytes(&bytes)?],
+        )))
+    }
+
+    /// Simulate the proof verification without committing it on chain
+    pub async fn simulate_proof(
+        &self,
+        payload: &PullResponseAptos,
+    ) -> Result<AptosSimulation, ConnectorError> {
+        let account = Account::from_secret_key(self.secret_key.clone())?;
+        let aptos_arg = self.proof_payload(payload)?;
+        let sequence_number = self
+            .client
+            .with_aptos(|aptos| account.get_seq_num(aptos))
+            .await?;
+        let gas_unit_price = self.gas_unit_price().await?;
+        let simulation = self
+            .simulate(
+                &account,
+                &aptos_arg,
+                sequence_number,
+                gas_unit_price,
+                self.gas_budget,
+            )
+            .await?;
+        Ok(AptosSimulation {
+            success: simulation.info.success,
+            vm_status: simulation.info.vm_status,
+            gas_used: simulation.info.gas_used.0,
+            events: simulation.events,
+        })
+    }
+
+    async fn simulate(
+        &self,
+        account: &Account,
+        payload: &TransactionPayload,
+        sequence_number: u64,
+        gas_unit_price: u64,
+        max_gas_amount: u64,
+    ) -> Result<UserTransaction, ConnectorError> {
+        let tx = self
+            .client
+            .with_aptos(|aptos| {
+                account.setup_simulation(
+                    payload,
+                    aptos,
+                    sequence_number,
+                    gas_unit_price,
+                    max_gas_amount,
+                )
+            })
+            .await?;
+        self.client
+            .with_aptos(|aptos| aptos.simulate(&tx))
+            .await?
+            .into_inner()
+            .pop()
+            .ok_or_else(|| ConnectorError::Simulation("empty simulation response".to_string()))
+    }
+}
+
+/// Whether the node answered the submission with an error, as opposed to a transport failure
+/// after which the transaction may or may not have been accepted
+fn is_rejection(err: &ConnectorError) -> bool {
+    matches!(err, ConnectorError::Rest(RestError::Api(_)))
+}
+
+/// Whether the node rejected a transaction because of its sequence number
+fn is_sequence_number_rejection(err: &ConnectorError) -> bool {
+    let ConnectorError::Rest(RestError::Api(response)) = err else {
+        return false;
+    };
+    matches!(
+        response
+            .error
+            .vm_error_code
+            .and_then(|code| StatusCode::try_from(code).ok()),
+        Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD | StatusCode::SEQUENCE_NUMBER_TOO_NEW)
+    )
+}
+
+fn identifier(name: &str) -> Result<Identifier, ConnectorError> {
+    Identifier::new(name).map_err(|_| ConnectorError::InvalidIdentifier(name.to_string()))
 }
 
 #[derive(Clone)]
@@ -164,9 +404,9 @@ pub struct Account {
 impl Account {
     pub fn from_secret_key(input: String) -> Result<Self, ConnectorError> {
         let input = input.trim_start_matches("0x");
-        let h = hex::decode(input).unwrap();
-        let signing_key = SecretKey::from_bytes(&h).unwrap();
-        let sender_key = Ed25519PrivateKey::try_from(&*signing_key.to_bytes().to_vec()).unwrap();
+        let h = hex::decode(input)?;
+        let signing_key = SecretKey::from_bytes(&h)?;
+        let sender_key = Ed25519PrivateKey::try_from(&*signing_key.to_bytes().to_vec())?;
         Ok(Account {
             signing_key,
             sender_key,
@@ -202,11 +442,12 @@ impl Account {
         payload: &TransactionPayload,
         rest_client: &Client,
         sequence_number: u64,
-        gas_budget: u64,
+        gas_unit_price: u64,
+        max_gas_amount: u64,
     ) -> Result<SignedTransaction, ConnectorError> {
-        let transaction_factory = TransactionFactory::new(chain_id(rest_client).await.unwrap())
-            .with_gas_unit_price(100)
-            .with_max_gas_amount(gas_budget);
+        let transaction_factory = TransactionFactory::new(chain_id(rest_client).await?)
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(max_gas_amount);
 
         let sender_key = self.sender_key.clone();
         let sender_account =
@@ -214,4 +455,30 @@ impl Account {
         Ok(sender_account
             .sign_with_transaction_builder(transaction_factory.payload(payload.clone())))
     }
+
+    /// Constructs a transaction from a payload with a zeroed signature, as expected by the
+    /// simulate endpoint
+    pub async fn setup_simulation(
+        &self,
+        payload: &TransactionPayload,
+        rest_client: &Client,
+        sequence_number: u64,
+        gas_unit_price: u64,
+        max_gas_amount: u64,
+    ) -> Result<SignedTransaction, ConnectorError> {
+        let transaction_factory = TransactionFactory::new(chain_id(rest_client).await?)
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(max_gas_amount);
+        let raw_tx = transaction_factory
+            .payload(payload.clone())
+            .sender(self.to_address()?)
+            .sequence_number(sequence_number)
+            .build();
+        let signature = Ed25519Signature::try_from(&[0u8; 64][..])?;
+        Ok(SignedTransaction::new(
+            raw_tx,
+            self.sender_key.public_key(),
+            signature,
+        ))
+    }
 }
@@ -1,5 +1,7 @@
 use aptos::common::types::CliError;
+use aptos_sdk::crypto::CryptoMaterialError;
 use aptos_sdk::move_types::account_address::AccountAddressParseError;
+use aptos_sdk::rest_client::error::RestError;
 use thiserror::Error;
 
 #[allow(missing_docs)]
@@ -11,4 +13,18 @@ pub enum ConnectorError {
     MoveAccountAddressParse(#[from] AccountAddressParseError),
     #[error(transparent)]
     Cli(#[from] CliError),
+    #[error(transparent)]
+    Rest(#[from] RestError),
+    #[error(transparent)]
+    CryptoMaterial(#[from] CryptoMaterialError),
+    #[error(transparent)]
+    FromHex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    Signature(#[from] ed25519_dalek::SignatureError),
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+    #[error("invalid move identifier: {0}")]
+    InvalidIdentifier(String),
+    #[error("transaction simulation failed: {0}")]
+    Simulation(String),
 }
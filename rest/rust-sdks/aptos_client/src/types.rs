@@ -1,3 +1,4 @@
+use aptos_sdk::rest_client::aptos_api_types::Event;
 use serde::{Deserialize, Serialize};
 
 /// Request for /get_proof rest api
@@ -15,3 +16,12 @@ pub struct PullResponseAptos {
     pub oracle_holder_object: String,
     pub proof_bytes: String,
 }
+
+/// Outcome of a transaction simulated against an aptos node
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AptosSimulation {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub events: Vec<Event>,
+}
@@ -1,4 +1,6 @@
-use cosmwasm_pull_client::cosmwasm_connector::invoke_cosmwasm_chain;
+use cosmwasm_pull_client::cosmwasm_connector::{
+    invoke_cosmwasm_chain, CosmWasmConfig, CosmWasmConnector,
+};
 use cosmwasm_pull_client::types::PullRequest;
 use cosmwasm_pull_client::Client;
 
@@ -7,6 +9,15 @@ async fn main() {
     let address = "<REST API SERVER ADDRESS>".to_string(); // Set the rest server address
     let client = Client::new(address).await.unwrap();
 
+    // The connector keeps its gRPC channel open, build it once and reuse it for every proof
+    let cosmwasm_connector = CosmWasmConnector::new(CosmWasmConfig::new(
+        "<PRIVATE KEY>",      // Your Private Key
+        "<GRPC URL>",         // Enter the GRPC URL of the network
+        "<CONTRACT ADDRESS>", // Address of your smart contract
+    ))
+    .await
+    .unwrap();
+
     // Create a PullRequest
     let request = PullRequest {
         pair_indexes: vec![0, 21, 61, 49], // Set the pair indexes as an array
@@ -16,7 +27,7 @@ async fn main() {
     // Call the get_proof function and handle the result
     match client.get_proof(&request).await {
         Ok(response) => {
-            invoke_cosmwasm_chain(response).await;
+            invoke_cosmwasm_chain(response, &cosmwasm_connector).await;
         }
         Err(status) => {
             eprint!("{:?}", status);
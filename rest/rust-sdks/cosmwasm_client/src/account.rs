@@ -0,0 +1,96 @@
+use crate::errors::CosmWasmConnectorError;
+use cosmrs::proto::cosmos::auth::v1beta1::{BaseAccount, ModuleAccount};
+use cosmrs::proto::cosmos::vesting::v1beta1::{
+    BaseVestingAccount, ContinuousVestingAccount, DelayedVestingAccount, PeriodicVestingAccount,
+    PermanentLockedAccount,
+};
+use cosmrs::proto::traits::Message;
+use cosmrs::Any;
+
+/// `injective.types.v1beta1.EthAccount` and `ethermint.types.v1.EthAccount`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EthAccount {
+    #[prost(message, optional, tag = "1")]
+    pub base_account: Option<BaseAccount>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub code_hash: Vec<u8>,
+}
+
+/// Decode the base account wrapped by any of the known account types
+pub fn decode_account(account: &Any) -> Result<BaseAccount, CosmWasmConnectorError> {
+    let value = account.value.as_slice();
+    let base_account = match account.type_url.as_str() {
+        "/cosmos.auth.v1beta1.BaseAccount" => Some(BaseAccount::decode(value)?),
+        "/cosmos.auth.v1beta1.ModuleAccount" => ModuleAccount::decode(value)?.base_account,
+        "/cosmos.vesting.v1beta1.BaseVestingAccount" => {
+            BaseVestingAccount::decode(value)?.base_account
+        }
+        "/cosmos.vesting.v1beta1.ContinuousVestingAccount" => {
+            vesting_base(ContinuousVestingAccount::decode(value)?.base_vesting_account)
+        }
+        "/cosmos.vesting.v1beta1.DelayedVestingAccount" => {
+            vesting_base(DelayedVestingAccount::decode(value)?.base_vesting_account)
+        }
+        "/cosmos.vesting.v1beta1.PeriodicVestingAccount" => {
+            vesting_base(PeriodicVestingAccount::decode(value)?.base_vesting_account)
+        }
+        "/cosmos.vesting.v1beta1.PermanentLockedAccount" => {
+            vesting_base(PermanentLockedAccount::decode(value)?.base_vesting_account)
+        }
+        "/injective.types.v1beta1.EthAccount" | "/ethermint.types.v1.EthAccount" => {
+            EthAccount::decode(value)?.base_account
+        }
+        type_url => {
+            return Err(CosmWasmConnectorError::UnknownAccountType(
+                type_url.to_string(),
+            ))
+        }
+    };
+    base_account.ok_or_else(|| CosmWasmConnectorError::UnknownAccountType(account.type_url.clone()))
+}
+
+fn vesting_base(base_vesting_account: Option<BaseVestingAccount>) -> Option<BaseAccount> {
+    base_vesting_account?.base_account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_account() -> BaseAccount {
+        BaseAccount {
+            address: "osmo1test".to_string(),
+            pub_key: None,
+            account_number: 7,
+            sequence: 3,
+        }
+    }
+
+    #[test]
+    fn decodes_wrapped_base_account() {
+        let account = Any {
+            type_url: "/cosmos.vesting.v1beta1.ContinuousVestingAccount".to_string(),
+            value: ContinuousVestingAccount {
+                base_vesting_account: Some(BaseVestingAccount {
+                    base_account: Some(base_account()),
+                    ..Default::default()
+                }),
+                start_time: 0,
+            }
+            .encode_to_vec(),
+        };
+        assert_eq!(decode_account(&account).unwrap(), base_account());
+    }
+
+    #[test]
+    fn rejects_unknown_account_type() {
+        let account = Any {
+            type_url: "/unknown.Account".to_string(),
+            value: base_account().encode_to_vec(),
+        };
+        assert!(matches!(
+            decode_account(&account),
+            Err(CosmWasmConnectorError::UnknownAccountType(_))
+        ));
+    }
+}
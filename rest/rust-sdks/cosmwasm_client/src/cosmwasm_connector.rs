@@ -1,101 +1,468 @@
+use crate::account::decode_account;
 use crate::errors::CosmWasmConnectorError;
+use crate::fee_market::osmosis_base_fee;
 use crate::PullResponseCosmWasm;
 use cosmrs::cosmwasm::MsgExecuteContract;
 use cosmrs::proto::cosmos::auth::v1beta1::query_client::QueryClient;
 use cosmrs::proto::cosmos::auth::v1beta1::{BaseAccount, QueryAccountRequest};
-use cosmrs::proto::traits::Message;
+use cosmrs::proto::cosmos::base::abci::v1beta1::{GasInfo, TxResponse};
+use cosmrs::proto::cosmos::tx::v1beta1::service_client::ServiceClient;
+use cosmrs::proto::cosmos::tx::v1beta1::{
+    BroadcastMode as ProtoBroadcastMode, BroadcastTxRequest, GetTxRequest, SimulateRequest,
+};
+use cosmrs::tendermint::chain;
 use cosmrs::{
     crypto::secp256k1,
-    rpc,
-    tx::{self, Fee, Msg, SignDoc, SignerInfo},
-    AccountId, Coin,
+    tx::{self, Body, Fee, Msg, Raw, SignDoc, SignerInfo},
+    AccountId, Coin, Denom,
 };
-use serde_json::json;
+use cw_client_example::msg::{ExecuteMsg, SupraPullExecuteMsg};
 use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::Instant;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Code;
 
-pub async fn invoke_cosmwasm_chain(cosmwasm: PullResponseCosmWasm) {
-    const CHAIN_ID: &str = "osmo-test-5";
-    const ACCOUNT_PREFIX: &str = "osmo";
-    const DENOM: &str = "uosmo";
-    const COIN_AMOUNT: u128 = 10000;
-    const GAS_LIMIT: u64 = 900000;
+const DEFAULT_CHAIN_ID: &str = "osmo-test-5";
+const DEFAULT_ACCOUNT_PREFIX: &str = "osmo";
+const DEFAULT_FEE_DENOM: &str = "uosmo";
+const DEFAULT_GAS_PRICE: f64 = 0.025; // Fee denom paid per gas unit
+const DEFAULT_GAS_LIMIT: u64 = 900000; // Upper bound of the simulated gas limit
+const DEFAULT_GAS_ADJUSTMENT: f64 = 1.3; // Multiplier applied to the simulated gas
+const DEFAULT_INCLUSION_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_INCLUSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
-    let rpc_url = "<RPC URL>"; // Rpc url for desired chain
-    let grpc_url = "<GRPC URL>"; //Enter the GRPC URL of the network
-    let secret_key = "<PRIVATE KEY>"; // Your Private Key
-    let contract_address = "<CONTRACT ADDRESS>"; // Address of your smart contract
+/// Contract the proof is submitted to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofTarget {
+    /// Consumer contract verifying the proof through the Supra pull contract and storing the prices
+    #[default]
+    Consumer,
+    /// Supra pull contract, verifying the proof directly
+    SupraPull,
+}
 
-    let sender_private_key =
-        secp256k1::SigningKey::from_slice(hex::decode(secret_key).unwrap().as_slice()).unwrap();
+impl ProofTarget {
+    /// Json execute message of the target contract, built from the contract's own message types
+    pub fn execute_msg(self, proof_bytes: Vec<u8>) -> Result<Vec<u8>, CosmWasmConnectorError> {
+        Ok(match self {
+            ProofTarget::Consumer => {
+                serde_json::to_vec(&ExecuteMsg::VerifyOracleProof { proof_bytes })?
+            }
+            ProofTarget::SupraPull => {
+                serde_json::to_vec(&SupraPullExecuteMsg::VerifyOracleProof {
+                    bytes_proof: proof_bytes,
+                })?
+            }
+        })
+    }
+}
 
-    let sender_public_key = sender_private_key.public_key();
-    let sender_account_id = sender_public_key.account_id(ACCOUNT_PREFIX).unwrap();
-    println!("osmosis account: {:?}", sender_account_id);
+/// How long the node is waited on when broadcasting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BroadcastMode {
+    /// Wait for the CheckTx result, failures are returned right away
+    #[default]
+    Sync,
+    /// Return as soon as the node received the transaction
+    Async,
+}
+
+/// How the price paid per gas unit is picked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GasPricePolicy {
+    /// Fixed price in fee denom per gas unit
+    Fixed(f64),
+    /// Osmosis EIP-1559 base fee times `multiplier`, `fallback` where the chain has no fee market
+    OsmosisBaseFee { multiplier: f64, fallback: f64 },
+}
+
+impl Default for GasPricePolicy {
+    fn default() -> Self {
+        GasPricePolicy::Fixed(DEFAULT_GAS_PRICE)
+    }
+}
 
-    let (account_number, sequence_number) =
-        collect_acc_sequence_number(grpc_url.to_string(), sender_account_id.clone())
-            .await
-            .expect("Unable to fetch account and sequence number");
+pub async fn invoke_cosmwasm_chain(
+    cosmwasm: PullResponseCosmWasm,
+    cosmwasm_connector: &CosmWasmConnector,
+) {
+    println!("account: {:?}", cosmwasm_connector.account_id());
+    match cosmwasm_connector.submit_proof(&cosmwasm).await {
+        Ok(tx_response) => println!("tx_response:{:?}", tx_response),
+        Err(err) => println!("{:?}", err),
+    }
+}
 
-    let rpc_client = rpc::client::HttpClient::new(rpc_url).unwrap();
+pub struct CosmWasmConfig<'a> {
+    secret_key: &'a str,
+    grpc_url: &'a str,
+    contract_address: &'a str,
+    target: ProofTarget,
+    chain_id: &'a str,
+    account_prefix: &'a str,
+    fee_denom: &'a str,
+    gas_price: GasPricePolicy,
+    gas_limit: u64,
+    gas_adjustment: f64,
+    broadcast_mode: BroadcastMode,
+    inclusion_timeout: Duration,
+    inclusion_poll_interval: Duration,
+}
 
-    let contract_account_id = AccountId::from_str(contract_address).unwrap();
-    let execute_msg = json!({
-        "verify_oracle_proof": {
-            "bytes_proof" : cosmwasm.proof_bytes,
+impl<'a> CosmWasmConfig<'a> {
+    /// Configured for the osmosis testnet, use `with_chain` for any other cosmos sdk chain
+    pub fn new(secret_key: &'a str, grpc_url: &'a str, contract_address: &'a str) -> Self {
+        Self {
+            secret_key,
+            grpc_url,
+            contract_address,
+            target: ProofTarget::default(),
+            chain_id: DEFAULT_CHAIN_ID,
+            account_prefix: DEFAULT_ACCOUNT_PREFIX,
+            fee_denom: DEFAULT_FEE_DENOM,
+            gas_price: GasPricePolicy::default(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            gas_adjustment: DEFAULT_GAS_ADJUSTMENT,
+            broadcast_mode: BroadcastMode::default(),
+            inclusion_timeout: DEFAULT_INCLUSION_TIMEOUT,
+            inclusion_poll_interval: DEFAULT_INCLUSION_POLL_INTERVAL,
         }
-    });
-
-    let contract_msg = MsgExecuteContract {
-        sender: sender_account_id.clone(),
-        contract: contract_account_id,
-        msg: serde_json::to_vec(&execute_msg).unwrap(),
-        funds: vec![],
-    };
-    let tx_body = tx::BodyBuilder::new()
-        .msg(contract_msg.to_any().unwrap())
-        .finish();
-
-    let fee = Fee {
-        amount: vec![Coin {
-            amount: COIN_AMOUNT,
-            denom: DENOM.parse().unwrap(),
-        }],
-        gas_limit: GAS_LIMIT,
-        payer: Some(sender_account_id),
-        granter: None,
-    };
-    let auth_info =
-        SignerInfo::single_direct(Some(sender_public_key), sequence_number).auth_info(fee);
-    let sign_doc = SignDoc::new(
-        &tx_body,
-        &auth_info,
-        &CHAIN_ID.parse().unwrap(),
-        account_number,
-    )
-    .unwrap();
-    let tx_raw = sign_doc.sign(&sender_private_key).unwrap();
-    let tx_response = tx_raw.broadcast_commit(&rpc_client).await.unwrap();
-    println!("tx_response:{:?}", tx_response);
+    }
+
+    /// Whether `contract_address` is the consumer contract or the Supra pull contract
+    pub fn with_target(mut self, target: ProofTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Chain id, bech32 account prefix and denom the fee is paid in
+    pub fn with_chain(
+        mut self,
+        chain_id: &'a str,
+        account_prefix: &'a str,
+        fee_denom: &'a str,
+    ) -> Self {
+        self.chain_id = chain_id;
+        self.account_prefix = account_prefix;
+        self.fee_denom = fee_denom;
+        self
+    }
+
+    /// Fixed fee denom paid per gas unit
+    pub fn with_gas_price(mut self, gas_price: f64) -> Self {
+        self.gas_price = GasPricePolicy::Fixed(gas_price);
+        self
+    }
+
+    pub fn with_gas_price_policy(mut self, gas_price: GasPricePolicy) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// Upper bound of the gas limit, the limit itself is sized by simulation
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Multiplier applied to the simulated gas
+    pub fn with_gas_adjustment(mut self, gas_adjustment: f64) -> Self {
+        self.gas_adjustment = gas_adjustment;
+        self
+    }
+
+    pub fn with_broadcast_mode(mut self, broadcast_mode: BroadcastMode) -> Self {
+        self.broadcast_mode = broadcast_mode;
+        self
+    }
+
+    /// How long the transaction is polled for before giving up on its inclusion
+    pub fn with_inclusion_timeout(mut self, inclusion_timeout: Duration) -> Self {
+        self.inclusion_timeout = inclusion_timeout;
+        self
+    }
+
+    pub fn with_inclusion_poll_interval(mut self, inclusion_poll_interval: Duration) -> Self {
+        self.inclusion_poll_interval = inclusion_poll_interval;
+        self
+    }
 }
 
-async fn collect_acc_sequence_number(
-    grpc_url: String,
+pub struct CosmWasmConnector {
+    grpc_channel: Channel,
+    signing_key: secp256k1::SigningKey,
     account_id: AccountId,
-) -> Result<(u64, u64), CosmWasmConnectorError> {
-    let grpc_url = grpc_url.clone();
-    let end_point = tonic::transport::Endpoint::new(grpc_url).unwrap();
-    let mut grpc_client = QueryClient::connect(end_point).await.unwrap();
-    let query = QueryAccountRequest {
-        address: account_id.to_string(),
-    };
-
-    match grpc_client.account(query).await {
-        Ok(resp) => {
-            let resp_account = resp.into_inner().account.unwrap_or(Default::default());
-            let account = BaseAccount::decode(resp_account.value.as_slice()).unwrap();
-            Ok((account.account_number, account.sequence))
+    contract: AccountId,
+    target: ProofTarget,
+    chain_id: chain::Id,
+    fee_denom: Denom,
+    gas_price: GasPricePolicy,
+    gas_limit: u64,
+    gas_adjustment: f64,
+    broadcast_mode: BroadcastMode,
+    inclusion_timeout: Duration,
+    inclusion_poll_interval: Duration,
+}
+
+impl CosmWasmConnector {
+    pub async fn new(conf: CosmWasmConfig<'_>) -> Result<Self, CosmWasmConnectorError> {
+        log::trace!("Start CosmWasmConnector");
+        let signing_key = secp256k1::SigningKey::from_slice(&hex::decode(conf.secret_key)?)
+            .map_err(|_| CosmWasmConnectorError::InvalidSecretKey)?;
+        let account_id = signing_key.public_key().account_id(conf.account_prefix)?;
+        let contract = AccountId::from_str(conf.contract_address)?;
+        let chain_id = chain::Id::from_str(conf.chain_id)
+            .map_err(|e| CosmWasmConnectorError::InvalidConfig(e.to_string()))?;
+        let fee_denom = Denom::from_str(conf.fee_denom)?;
+
+        Ok(Self {
+            grpc_channel: Endpoint::from_str(conf.grpc_url)?.connect().await?,
+            signing_key,
+            account_id,
+            contract,
+            target: conf.target,
+            chain_id,
+            fee_denom,
+            gas_price: conf.gas_price,
+            gas_limit: conf.gas_limit,
+            gas_adjustment: conf.gas_adjustment,
+            broadcast_mode: conf.broadcast_mode,
+            inclusion_timeout: conf.inclusion_timeout,
+            inclusion_poll_interval: conf.inclusion_poll_interval,
+        })
+    }
+
+    /// Account of the signing key
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// Execute the proof verification on the contract with a simulated gas limit, returning the
+    /// transaction once included in a block
+    pub async fn submit_proof(
+        &self,
+        payload: &PullResponseCosmWasm,
+    ) -> Result<TxResponse, CosmWasmConnectorError> {
+        let (account_number, sequence_number) = self.collect_acc_sequence_number().await?;
+
+        let contract_msg = MsgExecuteContract {
+            sender: self.account_id.clone(),
+            contract: self.contract.clone(),
+            msg: self
+                .target
+                .execute_msg(hex::decode(&payload.proof_bytes)?)?,
+            funds: vec![],
+        };
+        let tx_body = tx::BodyBuilder::new().msg(contract_msg.to_any()?).finish();
+
+        let simulation_tx = self.sign_tx(
+            &tx_body,
+            account_number,
+            sequence_number,
+            self.fee(self.gas_limit, 0.0),
+        )?;
+        let gas_limit = self.estimate_gas(simulation_tx).await?;
+        let gas_price = self.gas_price().await;
+        let tx_raw = self.sign_tx(
+            &tx_body,
+            account_number,
+            sequence_number,
+            self.fee(gas_limit, gas_price),
+        )?;
+        let tx_hash = self.broadcast(tx_raw).await?;
+        self.wait_for_inclusion(&tx_hash).await
+    }
+
+    /// Broadcast the transaction with the configured mode, returning its hash
+    pub async fn broadcast(&self, tx_raw: Raw) -> Result<String, CosmWasmConnectorError> {
+        let mode = match self.broadcast_mode {
+            BroadcastMode::Sync => ProtoBroadcastMode::Sync,
+            BroadcastMode::Async => ProtoBroadcastMode::Async,
+        };
+        let mut tx_client = ServiceClient::new(self.grpc_channel.clone());
+        let response = tx_client
+            .broadcast_tx(BroadcastTxRequest {
+                tx_bytes: tx_raw.to_bytes()?,
+                mode: mode as i32,
+            })
+            .await?
+            .into_inner()
+            .tx_response
+            .ok_or(CosmWasmConnectorError::InvalidGRPCResponse)?;
+        if response.code != 0 {
+            return Err(CosmWasmConnectorError::CheckTx {
+                codespace: response.codespace,
+                code: response.code,
+                log: response.raw_log,
+            });
+        }
+        log::debug!("cosmwasm: broadcast {}", response.txhash);
+        Ok(response.txhash)
+    }
+
+    /// Poll the transaction until it is included in a block or the inclusion timeout expires
+    pub async fn wait_for_inclusion(
+        &self,
+        tx_hash: &str,
+    ) -> Result<TxResponse, CosmWasmConnectorError> {
+        let deadline = Instant::now() + self.inclusion_timeout;
+        loop {
+            match self.tx_response(tx_hash).await {
+                Ok(response) if response.code != 0 => {
+                    return Err(CosmWasmConnectorError::DeliverTx {
+                        tx_hash: response.txhash,
+                        codespace: response.codespace,
+                        code: response.code,
+                        log: response.raw_log,
+                    });
+                }
+                Ok(response) => return Ok(response),
+                // Not in a block yet
+                Err(CosmWasmConnectorError::Grpc(status)) if status.code() == Code::NotFound => {}
+                Err(err) => return Err(err),
+            }
+            if Instant::now() + self.inclusion_poll_interval > deadline {
+                return Err(CosmWasmConnectorError::InclusionTimeout(
+                    tx_hash.to_string(),
+                ));
+            }
+            tokio::time::sleep(self.inclusion_poll_interval).await;
+        }
+    }
+
+    /// Run the signed transaction without broadcasting it
+    pub async fn simulate(&self, tx_raw: Raw) -> Result<GasInfo, CosmWasmConnectorError> {
+        let mut tx_client = ServiceClient::new(self.grpc_channel.clone());
+        #[allow(deprecated)]
+        let request = SimulateRequest {
+            tx: None,
+            tx_bytes: tx_raw.to_bytes()?,
+        };
+        let response = tx_client.simulate(request).await?;
+        response
+            .into_inner()
+            .gas_info
+            .ok_or(CosmWasmConnectorError::InvalidGRPCResponse)
+    }
+
+    /// Simulated gas used times the gas adjustment, the configured gas limit is the upper bound
+    pub async fn estimate_gas(&self, tx_raw: Raw) -> Result<u64, CosmWasmConnectorError> {
+        let required = self.simulate(tx_raw).await?.gas_used;
+        if required > self.gas_limit {
+            return Err(CosmWasmConnectorError::GasLimitExceeded {
+                required,
+                limit: self.gas_limit,
+            });
+        }
+        let gas_limit = (required as f64 * self.gas_adjustment).ceil() as u64;
+        log::debug!("cosmwasm: simulated gas {required}, gas limit {gas_limit}");
+        Ok(gas_limit.min(self.gas_limit))
+    }
+
+    /// Price paid per gas unit, following the gas price policy
+    pub async fn gas_price(&self) -> f64 {
+        match self.gas_price {
+            GasPricePolicy::Fixed(gas_price) => gas_price,
+            GasPricePolicy::OsmosisBaseFee {
+                multiplier,
+                fallback,
+            } => match osmosis_base_fee(self.grpc_channel.clone()).await {
+                Ok(base_fee) => base_fee * multiplier,
+                Err(err) => {
+                    log::debug!("cosmwasm: no osmosis base fee, using fallback: {err}");
+                    fallback
+                }
+            },
         }
-        Err(_) => Err(CosmWasmConnectorError::InvalidGRPCResponse),
+    }
+
+    fn fee(&self, gas_limit: u64, gas_price: f64) -> Fee {
+        Fee {
+            amount: vec![Coin {
+                amount: (gas_limit as f64 * gas_price).ceil() as u128,
+                denom: self.fee_denom.clone(),
+            }],
+            gas_limit,
+            payer: Some(self.account_id.clone()),
+            granter: None,
+        }
+    }
+
+    fn sign_tx(
+        &self,
+        tx_body: &Body,
+        account_number: u64,
+        sequence_number: u64,
+        fee: Fee,
+    ) -> Result<Raw, CosmWasmConnectorError> {
+        let auth_info =
+            SignerInfo::single_direct(Some(self.signing_key.public_key()), sequence_number)
+                .auth_info(fee);
+        let sign_doc = SignDoc::new(tx_body, &auth_info, &self.chain_id, account_number)?;
+        Ok(sign_doc.sign(&self.signing_key)?)
+    }
+
+    /// Look a transaction up by hash
+    pub async fn tx_response(&self, tx_hash: &str) -> Result<TxResponse, CosmWasmConnectorError> {
+        let mut tx_client = ServiceClient::new(self.grpc_channel.clone());
+        let response = tx_client
+            .get_tx(GetTxRequest {
+                hash: tx_hash.to_string(),
+            })
+            .await?;
+        response
+            .into_inner()
+            .tx_response
+            .ok_or(CosmWasmConnectorError::InvalidGRPCResponse)
+    }
+
+    async fn collect_acc_sequence_number(&self) -> Result<(u64, u64), CosmWasmConnectorError> {
+        let account = self.base_account().await?;
+        Ok((account.account_number, account.sequence))
+    }
+
+    /// Query the signer account and unwrap its base account whatever the account type is
+    pub async fn base_account(&self) -> Result<BaseAccount, CosmWasmConnectorError> {
+        let mut grpc_client = QueryClient::new(self.grpc_channel.clone());
+        let query = QueryAccountRequest {
+            address: self.account_id.to_string(),
+        };
+
+        let account = match grpc_client.account(query).await {
+            Ok(resp) => resp.into_inner().account,
+            Err(status) if status.code() == tonic::Code::NotFound => None,
+            Err(status) => return Err(status.into()),
+        };
+        let account = account
+            .ok_or_else(|| CosmWasmConnectorError::AccountNotFound(self.account_id.to_string()))?;
+        decode_account(&account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumer_execute_msg_round_trips() {
+        let msg = ProofTarget::Consumer.execute_msg(vec![1, 2, 3]).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<ExecuteMsg>(&msg).unwrap(),
+            ExecuteMsg::VerifyOracleProof {
+                proof_bytes: vec![1, 2, 3]
+            }
+        );
+    }
+
+    #[test]
+    fn supra_pull_execute_msg_round_trips() {
+        let msg = ProofTarget::SupraPull.execute_msg(vec![1, 2, 3]).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<SupraPullExecuteMsg>(&msg).unwrap(),
+            SupraPullExecuteMsg::VerifyOracleProof {
+                bytes_proof: vec![1, 2, 3]
+            }
+        );
     }
 }
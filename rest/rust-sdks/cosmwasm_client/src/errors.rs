@@ -5,4 +5,53 @@ use thiserror::Error;
 pub enum CosmWasmConnectorError {
     #[error("invalid grpc response")]
     InvalidGRPCResponse,
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+    #[error(transparent)]
+    FromHex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+    #[error(transparent)]
+    Grpc(Box<tonic::Status>),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("gas limit exceeded, simulation requires {required} but limit is {limit}")]
+    GasLimitExceeded { required: u64, limit: u64 },
+    #[error("check tx failed with code {code} in {codespace}: {log}")]
+    CheckTx {
+        codespace: String,
+        code: u32,
+        log: String,
+    },
+    #[error("tx {tx_hash} failed with code {code} in {codespace}: {log}")]
+    DeliverTx {
+        tx_hash: String,
+        codespace: String,
+        code: u32,
+        log: String,
+    },
+    #[error("tx {0} not included before the timeout")]
+    InclusionTimeout(String),
+    #[error("account {0} not found")]
+    AccountNotFound(String),
+    #[error("unknown account type {0}")]
+    UnknownAccountType(String),
+    #[error(transparent)]
+    Decode(#[from] prost::DecodeError),
+    #[error("cosmos sdk error: {0}")]
+    Cosmrs(String),
+}
+
+impl From<cosmrs::ErrorReport> for CosmWasmConnectorError {
+    fn from(err: cosmrs::ErrorReport) -> Self {
+        CosmWasmConnectorError::Cosmrs(err.to_string())
+    }
+}
+
+impl From<tonic::Status> for CosmWasmConnectorError {
+    fn from(status: tonic::Status) -> Self {
+        CosmWasmConnectorError::Grpc(Box::new(status))
+    }
 }
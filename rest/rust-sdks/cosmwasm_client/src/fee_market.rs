@@ -0,0 +1,44 @@
+use crate::errors::CosmWasmConnectorError;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::Channel;
+
+const OSMOSIS_EIP_BASE_FEE_PATH: &str = "/osmosis.txfees.v1beta1.Query/GetEipBaseFee";
+const DEC_PRECISION: i32 = 18; // Decimals of a cosmos sdk `Dec` serialized as an integer
+
+/// `osmosis.txfees.v1beta1.QueryEipBaseFeeRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryEipBaseFeeRequest {}
+
+/// `osmosis.txfees.v1beta1.QueryEipBaseFeeResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryEipBaseFeeResponse {
+    #[prost(string, tag = "1")]
+    pub base_fee: String,
+}
+
+/// Current base fee of the osmosis EIP-1559 fee market, per gas unit
+pub async fn osmosis_base_fee(channel: Channel) -> Result<f64, CosmWasmConnectorError> {
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await?;
+    let response = grpc
+        .unary(
+            tonic::Request::new(QueryEipBaseFeeRequest {}),
+            PathAndQuery::from_static(OSMOSIS_EIP_BASE_FEE_PATH),
+            ProstCodec::<QueryEipBaseFeeRequest, QueryEipBaseFeeResponse>::default(),
+        )
+        .await?;
+    parse_dec(&response.into_inner().base_fee)
+}
+
+/// A `Dec` is serialized either as a decimal string or as an integer scaled by 10^18
+fn parse_dec(value: &str) -> Result<f64, CosmWasmConnectorError> {
+    let dec = value
+        .parse::<f64>()
+        .map_err(|_| CosmWasmConnectorError::InvalidGRPCResponse)?;
+    if value.contains('.') {
+        Ok(dec)
+    } else {
+        Ok(dec / 10f64.powi(DEC_PRECISION))
+    }
+}
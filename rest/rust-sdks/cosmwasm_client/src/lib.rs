@@ -1,5 +1,7 @@
+pub mod account;
 pub mod cosmwasm_connector;
 pub mod errors;
+pub mod fee_market;
 pub mod types;
 
 use crate::types::PullResponseCosmWasm;
@@ -1,4 +1,4 @@
-use radix_pull_client::radix_connector::invoke_radix_chain;
+use radix_pull_client::radix_connector::{invoke_radix_chain, RadixConfig, RadixConnector};
 use radix_pull_client::types::{PullRequest, PullResponseRadix};
 use radix_pull_client::Client;
 
@@ -25,5 +25,13 @@ async fn main() {
 }
 //
 async fn call_contract(input: PullResponseRadix) {
-    invoke_radix_chain(input).await
+    let radix_connector = RadixConnector::new(RadixConfig::new(
+        "<PRIVATE_KEY>",
+        "https://stokenet.radixdlt.com", // Gateway of the network, see `RadixConfig::with_network`
+        "<COMPONENT_ADDRESS>",
+        "<COMPONENT METHOD>",
+        "{<NONFUNGIBLE-RUID>}",
+    ))
+    .unwrap();
+    invoke_radix_chain(input, radix_connector).await
 }
@@ -0,0 +1,43 @@
+use crate::gateway::TransactionError;
+use reqwest::StatusCode;
+use thiserror::Error;
+use transaction::prelude::Decimal;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ConnectorError {
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+    #[error("invalid component address: {0}")]
+    InvalidComponentAddress(String),
+    #[error("invalid non fungible id: {0}")]
+    InvalidNonFungibleId(String),
+    #[error(transparent)]
+    FromHex(#[from] hex::FromHexError),
+    #[error("unable to build transaction: {0}")]
+    TransactionBuild(String),
+    #[error(transparent)]
+    Gateway(#[from] GatewayError),
+    #[error("transaction {0} not committed before the timeout")]
+    CommitTimeout(String),
+    #[error("transaction preview failed: {0}")]
+    PreviewFailed(String),
+    #[error("lock fee exceeded, preview requires {required} but lock fee is {lock_fee}")]
+    LockFeeExceeded {
+        required: Decimal,
+        lock_fee: Decimal,
+    },
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum GatewayError {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("gateway error {}: {} {:?}", .0.code, .0.message, .0.details)]
+    Transaction(TransactionError),
+    #[error("unable to decode gateway response: {0}")]
+    Decode(String),
+    #[error("unexpected gateway response {status}: {body}")]
+    UnexpectedResponse { status: StatusCode, body: String },
+}
@@ -1,5 +1,9 @@
-use reqwest::{blocking, header::*};
+use crate::errors::GatewayError;
+use reqwest::{header::*, Client};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
 use transaction::prelude::*;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -7,11 +11,11 @@ pub struct TransactionSubmit {
     pub duplicate: bool,
 }
 
+/// Async client of the Radix gateway api
 #[derive(Clone, Debug)]
-pub struct GatewayApiClient {
+pub struct GatewayClient {
     url: String,
-    client: blocking::Client,
-    //  client: Client,
+    client: Client,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -45,6 +49,7 @@ pub struct TransactionDetails {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransactionReceipt {
     pub status: String,
+    pub fee_summary: Option<FeeSummary>,
     pub output: Option<Vec<TransactionOutput>>,
     pub error_message: Option<String>,
 }
@@ -52,7 +57,50 @@ pub struct TransactionReceipt {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransactionOutput {
     pub hex: String,
-    pub programmatic_json: Option<String>,
+    pub programmatic_json: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeSummary {
+    pub execution_cost_units_consumed: u64,
+    pub finalization_cost_units_consumed: u64,
+    pub xrd_total_execution_cost: String,
+    pub xrd_total_finalization_cost: String,
+    pub xrd_total_royalty_cost: String,
+    pub xrd_total_storage_cost: String,
+    pub xrd_total_tipping_cost: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionPreviewRequest {
+    pub manifest: String,
+    pub blobs_hex: Vec<String>,
+    pub start_epoch_inclusive: u64,
+    pub end_epoch_exclusive: u64,
+    pub notary_public_key: Option<GatewayPublicKey>,
+    pub notary_is_signatory: bool,
+    pub tip_percentage: u16,
+    pub nonce: u32,
+    pub signer_public_keys: Vec<GatewayPublicKey>,
+    pub flags: PreviewFlags,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PreviewFlags {
+    pub use_free_credit: bool,
+    pub assume_all_signature_proofs: bool,
+    pub skip_epoch_check: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GatewayPublicKey {
+    pub key_type: String,
+    pub key_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionPreview {
+    pub receipt: TransactionReceipt,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -60,8 +108,8 @@ pub struct TransactionError {
     // response 4xx
     pub message: String,
     pub code: u32,
-    pub details: ErrorDetails,
-    pub trace_id: String,
+    pub details: Option<ErrorDetails>,
+    pub trace_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -84,6 +132,19 @@ pub struct TransactionStatus {
     pub error_message: Option<String>,
 }
 
+/// Final status of a submitted intent
+#[derive(Debug)]
+pub enum CommitOutcome {
+    /// Committed and executed, with the receipt output and fee paid
+    Success(TransactionDetails),
+    /// Committed but the execution failed, the fee was still paid
+    Failure(TransactionDetails),
+    /// Can never be committed, with the rejection reason
+    PermanentlyRejected(Option<String>),
+    /// Rejected for now, it may still be committed until the end of its epoch window
+    LikelyRejected(Option<String>),
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct KnownPayloads {
     pub payload_hash: String,
@@ -108,6 +169,43 @@ pub struct TransactionDetailsStatus {
     pub receipt: TransactionReceipt,
 }
 
+impl TransactionReceipt {
+    /// Preview receipts report `Succeeded`, committed ones `CommittedSuccess`
+    pub fn is_success(&self) -> bool {
+        matches!(self.status.as_str(), "Succeeded" | "CommittedSuccess")
+    }
+}
+
+impl FeeSummary {
+    /// Total XRD cost of the transaction, `None` if a cost is not a decimal
+    pub fn total_cost(&self) -> Option<Decimal> {
+        [
+            &self.xrd_total_execution_cost,
+            &self.xrd_total_finalization_cost,
+            &self.xrd_total_royalty_cost,
+            &self.xrd_total_storage_cost,
+            &self.xrd_total_tipping_cost,
+        ]
+        .into_iter()
+        .try_fold(Decimal::ZERO, |total, cost| {
+            Some(total + Decimal::from_str(cost).ok()?)
+        })
+    }
+}
+
+impl From<&PublicKey> for GatewayPublicKey {
+    fn from(public_key: &PublicKey) -> Self {
+        let (key_type, key_hex) = match public_key {
+            PublicKey::Secp256k1(key) => ("EcdsaSecp256k1", hex::encode(key.to_vec())),
+            PublicKey::Ed25519(key) => ("EddsaEd25519", hex::encode(key.to_vec())),
+        };
+        Self {
+            key_type: key_type.to_string(),
+            key_hex,
+        }
+    }
+}
+
 impl TransactionDetails {
     pub fn get_output(&self, idx: usize) -> Option<String> {
         self.transaction
@@ -123,77 +221,91 @@ impl TransactionDetails {
     }
 }
 
-impl GatewayApiClient {
-    pub fn new(url: &str) -> Self {
-        Self {
-            url: url.to_string(),
-            client: blocking::Client::new(),
-        }
+impl GatewayClient {
+    /// Every request, including the connection, fails after `timeout`
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, GatewayError> {
+        let client = Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            client,
+        })
     }
 
-    pub fn gateway_status(&self) -> GatewayStatus {
-        let resp = self
-            .client
-            .post(self.url.clone() + "/status/gateway-status")
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/json")
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
-
-        let status: GatewayStatus = serde_json::from_str(&resp).unwrap();
-        status
+    pub async fn gateway_status(&self) -> Result<GatewayStatus, GatewayError> {
+        self.post("/status/gateway-status", &json!({})).await
     }
 
-    pub fn current_epoch(&self) -> u64 {
-        self.gateway_status().ledger_state.epoch
+    pub async fn current_epoch(&self) -> Result<u64, GatewayError> {
+        Ok(self.gateway_status().await?.ledger_state.epoch)
     }
 
-    pub fn transaction_submit(
+    pub async fn transaction_submit(
         &self,
-        transaction: NotarizedTransactionV1,
-    ) -> Result<TransactionSubmit, TransactionError> {
-        let notarized_transaction_bytes = transaction.to_payload_bytes().unwrap();
-        let notarized_transaction_hex = hex::encode(&notarized_transaction_bytes);
-
-        let mut map = HashMap::new();
-        map.insert("notarized_transaction_hex", notarized_transaction_hex);
+        notarized_transaction_hex: String,
+    ) -> Result<TransactionSubmit, GatewayError> {
+        self.post(
+            "/transaction/submit",
+            &json!({ "notarized_transaction_hex": notarized_transaction_hex }),
+        )
+        .await
+    }
 
-        let resp = self
-            .client
-            .post(self.url.clone() + "/transaction/submit")
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&map)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
+    pub async fn transaction_preview(
+        &self,
+        request: &TransactionPreviewRequest,
+    ) -> Result<TransactionPreview, GatewayError> {
+        self.post("/transaction/preview", request).await
+    }
 
-        serde_json::from_str::<TransactionSubmit>(&resp)
-            .map_err(|_| serde_json::from_str::<TransactionError>(&resp).unwrap())
+    pub async fn transaction_status(
+        &self,
+        intent_hash: &str,
+    ) -> Result<TransactionStatus, GatewayError> {
+        self.post(
+            "/transaction/status",
+            &json!({ "intent_hash": intent_hash }),
+        )
+        .await
     }
 
-    pub fn transaction_status(
+    /// Committed transaction with its receipt output and fee summary
+    pub async fn transaction_committed_details(
         &self,
         intent_hash: &str,
-    ) -> Result<TransactionStatus, TransactionError> {
-        let mut map = HashMap::new();
-        map.insert("intent_hash", intent_hash);
+    ) -> Result<TransactionDetails, GatewayError> {
+        self.post(
+            "/transaction/committed-details",
+            &json!({
+                "intent_hash": intent_hash,
+                "opt_ins": { "receipt_output": true, "receipt_fee_summary": true },
+            }),
+        )
+        .await
+    }
 
-        let resp = self
+    /// 4xx responses are decoded as `TransactionError`
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, GatewayError> {
+        let response = self
             .client
-            .post(self.url.clone() + "/transaction/status")
+            .post(format!("{}{}", self.url, path))
             .header(ACCEPT, "application/json")
             .header(CONTENT_TYPE, "application/json")
-            .json(&map)
+            .header(USER_AGENT, "oracle-pull-example")
+            .json(body)
             .send()
-            .unwrap()
-            .text()
-            .unwrap();
-
-        serde_json::from_str::<TransactionStatus>(&resp)
-            .map_err(|_| serde_json::from_str::<TransactionError>(&resp).unwrap())
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status.is_success() {
+            return serde_json::from_str(&body).map_err(|e| GatewayError::Decode(e.to_string()));
+        }
+        match serde_json::from_str::<TransactionError>(&body) {
+            Ok(error) => Err(GatewayError::Transaction(error)),
+            Err(_) => Err(GatewayError::UnexpectedResponse { status, body }),
+        }
     }
 }
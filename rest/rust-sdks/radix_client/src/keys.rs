@@ -0,0 +1,40 @@
+use crate::errors::ConnectorError;
+use transaction::prelude::*;
+
+/// Signature scheme of a key
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyType {
+    #[default]
+    Ed25519,
+    Secp256k1,
+}
+
+/// Private key of the notary or of an intent signer
+pub enum RadixKey {
+    Ed25519(Ed25519PrivateKey),
+    Secp256k1(Secp256k1PrivateKey),
+}
+
+impl RadixKey {
+    /// Parse a hex encoded private key
+    pub fn from_hex(secret_key: &str, key_type: KeyType) -> Result<Self, ConnectorError> {
+        let bytes = hex::decode(secret_key)?;
+        match key_type {
+            KeyType::Ed25519 => Ed25519PrivateKey::from_bytes(&bytes).map(RadixKey::Ed25519),
+            KeyType::Secp256k1 => Secp256k1PrivateKey::from_bytes(&bytes).map(RadixKey::Secp256k1),
+        }
+        .map_err(|_| ConnectorError::InvalidSecretKey)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            RadixKey::Ed25519(key) => key.public_key().into(),
+            RadixKey::Secp256k1(key) => key.public_key().into(),
+        }
+    }
+
+    /// Virtual account controlled by the key
+    pub fn account(&self) -> ComponentAddress {
+        ComponentAddress::virtual_account_from_public_key(&self.public_key())
+    }
+}
@@ -2,9 +2,12 @@ use crate::types::{PullRequest, PullResponseRadix};
 use reqwest::Client as HttpClient;
 use std::error::Error;
 
-mod gateway;
+pub mod errors;
+pub mod gateway;
+pub mod keys;
 pub mod radix_connector;
 pub mod types;
+pub mod utils;
 
 pub struct Client {
     client: HttpClient,
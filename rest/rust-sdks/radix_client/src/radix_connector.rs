@@ -1,8 +1,15 @@
-use crate::gateway::{GatewayStatus, TransactionStatus, TransactionSubmit};
+use crate::errors::ConnectorError;
+use crate::gateway::{
+    CommitOutcome, GatewayClient, GatewayPublicKey, PreviewFlags, TransactionPreviewRequest,
+    TransactionReceipt,
+};
+use crate::keys::{KeyType, RadixKey};
 use crate::types::PullResponseRadix;
-use reqwest::header::*;
-use reqwest::Client;
-use std::time::Duration;
+use crate::utils::create_notarized_transaction;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+use transaction::manifest::decompile;
 use transaction::prelude::*;
 
 pub type PairIndex = u32;
@@ -38,135 +45,431 @@ pub struct OracleProof {
     pub data: Vec<PriceDetailsWithCommittee>,
 }
 
-const GATEWAY_URL: &str = "https://stokenet.radixdlt.com";
-const NETWORK_ID: u8 = 2;
-const LOGICAL_NAME: &str = "stokenet";
-const HRP_SUFFIX: &str = "tdx_2_";
+pub async fn invoke_radix_chain(payload: PullResponseRadix, radix_connector: RadixConnector) {
+    let intent_hash = match radix_connector.submit_proof(&payload).await {
+        Ok(intent_hash) => intent_hash,
+        Err(err) => {
+            println!("{:?}", err);
+            return;
+        }
+    };
+    println!("Tx Hash: {:?}", intent_hash);
+    match radix_connector.wait_for_commit(&intent_hash).await {
+        Ok(CommitOutcome::Success(details)) => {
+            println!("Fee Paid: {}", details.transaction.fee_paid);
+            println!("Output: {:?}", details.get_output(1));
+        }
+        outcome => println!("Transaction Status:{:#?}", outcome),
+    }
+}
 
-pub async fn invoke_radix_chain(radix_response: PullResponseRadix) {
-    // let oracle_proof_bytes : OracleProof = scrypto_decode(&radix_response.proof_bytes).unwrap();
-    // println!("oracle proof: {:#?}", oracle_proof_bytes);
-    let mut oracle_proof_bytes = hex::decode(radix_response.proof_bytes).unwrap();
+const DEFAULT_LOCK_FEE: u32 = 10; // XRD locked for fees from the fee account
+const DEFAULT_EPOCH_WINDOW: u64 = 10; // Number of epochs the transaction stays valid
+const MAX_EPOCH_WINDOW: u64 = 8640; // Largest epoch range the ledger accepts, 30 days of 5 minute epochs
+const PREVIEW_FEE_MARGIN_PERCENT: u32 = 20; // Headroom added on top of the previewed fee
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_COMMIT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_COMMIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_COMMIT_POLL_INTERVAL: Duration = Duration::from_secs(10); // Upper bound of the polling backoff
 
-    let network_definition = NetworkDefinition {
-        id: NETWORK_ID,
-        logical_name: LOGICAL_NAME.to_string(),
-        hrp_suffix: HRP_SUFFIX.to_string(),
-    };
+/// How transaction header nonces are picked, two identical transactions within the same epoch
+/// window need different nonces
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonceStrategy {
+    /// Random nonce for every transaction
+    #[default]
+    Random,
+    /// Counter seeded from the connector creation time
+    Monotonic,
+}
 
-    let hash_encoder = TransactionHashBech32Encoder::new(&network_definition);
+pub struct RadixConfig<'a> {
+    secret_key: &'a str,
+    notary_key_type: KeyType,
+    notary_is_signatory: bool,
+    signers: Vec<(&'a str, KeyType)>,
+    fee_account: Option<&'a str>,
+    gateway_url: &'a str,
+    network: NetworkDefinition,
+    component_address: &'a str,
+    method: &'a str,
+    badge_id: &'a str,
+    lock_fee: Decimal,
+    nonce_strategy: NonceStrategy,
+    epoch_window: u64,
+    tip_percentage: u16,
+    request_timeout: Duration,
+    commit_timeout: Duration,
+    commit_poll_interval: Duration,
+}
 
-    let address_decoder = AddressBech32Decoder::new(&network_definition);
-    let address_encoder = AddressBech32Encoder::new(&network_definition);
+impl<'a> RadixConfig<'a> {
+    /// `method` of `component_address` is called with the proof bytes and the `badge_id` non
+    /// fungible, e.g. `{...}` for a ruid. `secret_key` is the hex encoded notary key.
+    pub fn new(
+        secret_key: &'a str,
+        gateway_url: &'a str,
+        component_address: &'a str,
+        method: &'a str,
+        badge_id: &'a str,
+    ) -> Self {
+        Self {
+            secret_key,
+            notary_key_type: KeyType::default(),
+            notary_is_signatory: true,
+            signers: vec![],
+            fee_account: None,
+            gateway_url,
+            network: NetworkDefinition::stokenet(),
+            component_address,
+            method,
+            badge_id,
+            lock_fee: Decimal::from(DEFAULT_LOCK_FEE),
+            nonce_strategy: NonceStrategy::default(),
+            epoch_window: DEFAULT_EPOCH_WINDOW,
+            tip_percentage: 0,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            commit_timeout: DEFAULT_COMMIT_TIMEOUT,
+            commit_poll_interval: DEFAULT_COMMIT_POLL_INTERVAL,
+        }
+    }
 
-    let component_address =
-        ComponentAddress::try_from_bech32(&address_decoder, "<COMPONENT_ADDRESS>")
-            .expect("Invalid component address");
+    /// Signature scheme of the notary key, Ed25519 by default
+    pub fn with_notary_key_type(mut self, notary_key_type: KeyType) -> Self {
+        self.notary_key_type = notary_key_type;
+        self
+    }
 
-    let client = Client::new();
-    let epoch = get_epoch(&client).await;
+    /// Whether the notary signature also counts as an intent signature, true by default
+    pub fn with_notary_is_signatory(mut self, notary_is_signatory: bool) -> Self {
+        self.notary_is_signatory = notary_is_signatory;
+        self
+    }
 
-    let mut index_set = IndexSet::new();
-    index_set.insert(NonFungibleLocalId::from_str("{<NONFUNGIBLE-RUID>}").unwrap());
+    /// Add an intent signer, e.g. the key of the account holding the badge
+    pub fn with_signer(mut self, secret_key: &'a str, key_type: KeyType) -> Self {
+        self.signers.push((secret_key, key_type));
+        self
+    }
 
-    let manifest = ManifestBuilder::new()
-        .lock_fee_from_faucet()
-        .call_method(
-            DynamicGlobalAddress::from(component_address),
-            "<COMPONENT METHOD>",
-            manifest_args!(oracle_proof_bytes, index_set),
-        )
-        .build();
+    /// Account the fee is locked from, the notary's account by default.
+    /// Its owner has to sign, either as an intent signer or as the notary being a signatory.
+    pub fn with_fee_account(mut self, fee_account: &'a str) -> Self {
+        self.fee_account = Some(fee_account);
+        self
+    }
 
-    let private_key =
-        Ed25519PrivateKey::from_bytes(&hex::decode("<PRIVATE_KEY>").unwrap()).unwrap();
-    let public_address =
-        ComponentAddress::virtual_account_from_public_key(&private_key.public_key());
+    /// Maximum amount of XRD locked for fees from the fee account, the fee actually locked
+    /// is sized from the transaction preview
+    pub fn with_lock_fee(mut self, lock_fee: Decimal) -> Self {
+        self.lock_fee = lock_fee;
+        self
+    }
 
-    let public_address_string = public_address.to_string(&address_encoder);
+    pub fn with_nonce_strategy(mut self, nonce_strategy: NonceStrategy) -> Self {
+        self.nonce_strategy = nonce_strategy;
+        self
+    }
 
-    println!("Account Address:{:?}", public_address_string);
+    /// Number of epochs, from the current one, the transaction can be committed in.
+    /// Clamped between 1 and the largest epoch range the ledger accepts.
+    pub fn with_epoch_window(mut self, epoch_window: u64) -> Self {
+        self.epoch_window = epoch_window.clamp(1, MAX_EPOCH_WINDOW);
+        self
+    }
 
-    let transaction = TransactionBuilder::new()
-        .header(TransactionHeaderV1 {
-            network_id: NETWORK_ID,
-            start_epoch_inclusive: Epoch::of(epoch),
-            end_epoch_exclusive: Epoch::of(epoch + 10),
-            nonce: 3,
-            notary_public_key: private_key.public_key().into(),
-            notary_is_signatory: false,
-            tip_percentage: 0,
+    /// Tip paid to validators, in percent of the fee
+    pub fn with_tip_percentage(mut self, tip_percentage: u16) -> Self {
+        self.tip_percentage = tip_percentage;
+        self
+    }
+
+    /// Set the timeout of gateway requests
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// How long `wait_for_commit` polls before giving up
+    pub fn with_commit_timeout(mut self, commit_timeout: Duration) -> Self {
+        self.commit_timeout = commit_timeout;
+        self
+    }
+
+    /// First polling interval of `wait_for_commit`, doubled after every poll
+    pub fn with_commit_poll_interval(mut self, commit_poll_interval: Duration) -> Self {
+        self.commit_poll_interval = commit_poll_interval;
+        self
+    }
+
+    /// Network of the gateway, stokenet by default.
+    /// Use `NetworkDefinition::mainnet()` or a custom definition for a localnet.
+    pub fn with_network(mut self, network: NetworkDefinition) -> Self {
+        self.network = network;
+        self
+    }
+}
+
+pub struct RadixConnector {
+    gateway: GatewayClient,
+    network: NetworkDefinition,
+    component_address: ComponentAddress,
+    method: String,
+    badge_id: NonFungibleLocalId,
+    notary: RadixKey,
+    notary_is_signatory: bool,
+    signers: Vec<RadixKey>,
+    fee_account: ComponentAddress,
+    lock_fee: Decimal,
+    nonce_strategy: NonceStrategy,
+    nonce: AtomicU32,
+    epoch_window: u64,
+    tip_percentage: u16,
+    commit_timeout: Duration,
+    commit_poll_interval: Duration,
+}
+
+impl RadixConnector {
+    pub fn new(conf: RadixConfig<'_>) -> Result<Self, ConnectorError> {
+        log::trace!("Start RadixConnector");
+        let address_decoder = AddressBech32Decoder::new(&conf.network);
+        let component_address =
+            ComponentAddress::try_from_bech32(&address_decoder, conf.component_address)
+                .ok_or_else(|| {
+                    ConnectorError::InvalidComponentAddress(conf.component_address.to_string())
+                })?;
+        let badge_id = NonFungibleLocalId::from_str(conf.badge_id)
+            .map_err(|_| ConnectorError::InvalidNonFungibleId(conf.badge_id.to_string()))?;
+        let notary = RadixKey::from_hex(conf.secret_key, conf.notary_key_type)?;
+        let signers = conf
+            .signers
+            .iter()
+            .map(|(secret_key, key_type)| RadixKey::from_hex(secret_key, *key_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        let fee_account = match conf.fee_account {
+            Some(fee_account) => ComponentAddress::try_from_bech32(&address_decoder, fee_account)
+                .ok_or_else(|| {
+                ConnectorError::InvalidComponentAddress(fee_account.to_string())
+            })?,
+            None => notary.account(),
+        };
+
+        Ok(Self {
+            gateway: GatewayClient::new(conf.gateway_url, conf.request_timeout)?,
+            network: conf.network,
+            component_address,
+            method: conf.method.to_string(),
+            badge_id,
+            notary,
+            notary_is_signatory: conf.notary_is_signatory,
+            signers,
+            fee_account,
+            lock_fee: conf.lock_fee,
+            nonce_strategy: conf.nonce_strategy,
+            nonce: AtomicU32::new(nonce_seed()),
+            epoch_window: conf.epoch_window,
+            tip_percentage: conf.tip_percentage,
+            commit_timeout: conf.commit_timeout,
+            commit_poll_interval: conf.commit_poll_interval,
         })
-        .manifest(manifest)
-        .notarize(&private_key)
-        .build();
+    }
 
-    let intent_hash = transaction.prepare().unwrap().intent_hash();
-    let notarized_transaction_bytes = transaction.to_payload_bytes().unwrap();
-    let notarized_transaction_hex = hex::encode(&notarized_transaction_bytes);
+    pub fn gateway(&self) -> &GatewayClient {
+        &self.gateway
+    }
+
+    /// Account paying the fees
+    pub fn account(&self) -> ComponentAddress {
+        self.fee_account
+    }
 
-    let tx_resp = transaction_submit(&client, notarized_transaction_hex).await;
+    pub fn account_address(&self) -> String {
+        self.account()
+            .to_string(&AddressBech32Encoder::new(&self.network))
+    }
+
+    fn next_nonce(&self) -> u32 {
+        match self.nonce_strategy {
+            NonceStrategy::Random => rand::random(),
+            NonceStrategy::Monotonic => self.nonce.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Preview the proof, then call the component method with the fee lock sized from the preview.
+    /// Returns the bech32 intent hash.
+    pub async fn submit_proof(
+        &self,
+        payload: &PullResponseRadix,
+    ) -> Result<String, ConnectorError> {
+        let receipt = self.preview_proof(payload).await?;
+        if !receipt.is_success() {
+            return Err(ConnectorError::PreviewFailed(
+                receipt
+                    .error_message
+                    .unwrap_or_else(|| receipt.status.clone()),
+            ));
+        }
+        let lock_fee = self.lock_fee_from_preview(&receipt)?;
+        let manifest = self.proof_manifest(payload, lock_fee)?;
+        let (transaction, intent_hash) = self.notarize(manifest).await?;
+        let notarized_transaction_bytes = transaction
+            .to_payload_bytes()
+            .map_err(|e| ConnectorError::TransactionBuild(format!("{e:?}")))?;
+
+        let tx_resp = self
+            .gateway
+            .transaction_submit(hex::encode(notarized_transaction_bytes))
+            .await?;
+        log::debug!(
+            "radix: submitted from {}, {:?}",
+            self.account_address(),
+            tx_resp
+        );
+        TransactionHashBech32Encoder::new(&self.network)
+            .encode(&intent_hash)
+            .map_err(|e| ConnectorError::TransactionBuild(format!("{e:?}")))
+    }
 
-    let intent_hash_str = hash_encoder.encode(&intent_hash).unwrap();
-    println!("Tx Hash: {:?}", intent_hash_str);
-    println!("Tx Resp: {:?}", tx_resp);
-    if !tx_resp.duplicate {
+    /// Poll the intent status until it is final, with backoff, fetching the committed details of
+    /// committed transactions
+    pub async fn wait_for_commit(
+        &self,
+        intent_hash: &str,
+    ) -> Result<CommitOutcome, ConnectorError> {
+        let deadline = Instant::now() + self.commit_timeout;
+        let mut interval = self.commit_poll_interval;
         loop {
-            let tx_status = transaction_status(&client, &intent_hash_str).await;
-            if tx_status.status != "Pending" {
-                println!("Transaction Status:{:#?}", tx_status);
-                break;
+            let status = self.gateway.transaction_status(intent_hash).await?;
+            log::debug!("radix: {intent_hash} is {}", status.intent_status);
+            match status.intent_status.as_str() {
+                "CommittedSuccess" => {
+                    let details = self
+                        .gateway
+                        .transaction_committed_details(intent_hash)
+                        .await?;
+                    return Ok(CommitOutcome::Success(details));
+                }
+                "CommittedFailure" => {
+                    let details = self
+                        .gateway
+                        .transaction_committed_details(intent_hash)
+                        .await?;
+                    return Ok(CommitOutcome::Failure(details));
+                }
+                "PermanentlyRejected" => {
+                    return Ok(CommitOutcome::PermanentlyRejected(status.error_message));
+                }
+                "LikelyButNotCertainRejection" => {
+                    return Ok(CommitOutcome::LikelyRejected(status.error_message));
+                }
+                _ => {}
             }
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            if Instant::now() + interval > deadline {
+                return Err(ConnectorError::CommitTimeout(intent_hash.to_string()));
+            }
+            tokio::time::sleep(interval).await;
+            interval = (interval * 2).min(MAX_COMMIT_POLL_INTERVAL);
         }
     }
-}
 
-pub async fn get_epoch(client: &Client) -> u64 {
-    let resp = client
-        .post(format!("{}/status/gateway-status", GATEWAY_URL))
-        .header(ACCEPT, "application/json")
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "oracle-pull-example")
-        .send()
-        .await
-        .expect("Unable to get status");
-
-    let body: GatewayStatus = resp.json().await.expect("Unable to parse json on status");
-    body.ledger_state.epoch
-}
+    /// Run the proof transaction, locking the maximum fee, without committing it
+    pub async fn preview_proof(
+        &self,
+        payload: &PullResponseRadix,
+    ) -> Result<TransactionReceipt, ConnectorError> {
+        let manifest = self.proof_manifest(payload, self.lock_fee)?;
+        let header = self.header().await?;
+        let request = TransactionPreviewRequest {
+            manifest: decompile(&manifest.instructions, &self.network)
+                .map_err(|e| ConnectorError::TransactionBuild(format!("{e:?}")))?,
+            blobs_hex: manifest.blobs.values().map(hex::encode).collect(),
+            start_epoch_inclusive: header.start_epoch_inclusive.number(),
+            end_epoch_exclusive: header.end_epoch_exclusive.number(),
+            notary_public_key: Some(GatewayPublicKey::from(&header.notary_public_key)),
+            notary_is_signatory: header.notary_is_signatory,
+            tip_percentage: header.tip_percentage,
+            nonce: header.nonce,
+            signer_public_keys: self
+                .signers
+                .iter()
+                .map(|signer| GatewayPublicKey::from(&signer.public_key()))
+                .collect(),
+            flags: PreviewFlags {
+                use_free_credit: false,
+                assume_all_signature_proofs: false,
+                skip_epoch_check: false,
+            },
+        };
+        let preview = self.gateway.transaction_preview(&request).await?;
+        log::debug!("radix: preview receipt {:?}", preview.receipt);
+        Ok(preview.receipt)
+    }
+
+    /// Previewed fee plus a margin, the configured lock fee is the upper bound
+    pub fn lock_fee_from_preview(
+        &self,
+        receipt: &TransactionReceipt,
+    ) -> Result<Decimal, ConnectorError> {
+        let required = receipt
+            .fee_summary
+            .as_ref()
+            .and_then(|fee_summary| fee_summary.total_cost())
+            .ok_or_else(|| ConnectorError::PreviewFailed("no fee summary".to_string()))?;
+        if required > self.lock_fee {
+            return Err(ConnectorError::LockFeeExceeded {
+                required,
+                lock_fee: self.lock_fee,
+            });
+        }
+        let lock_fee =
+            required * Decimal::from(100 + PREVIEW_FEE_MARGIN_PERCENT) / Decimal::from(100);
+        Ok(lock_fee.min(self.lock_fee))
+    }
+
+    pub fn proof_manifest(
+        &self,
+        payload: &PullResponseRadix,
+        lock_fee: Decimal,
+    ) -> Result<TransactionManifestV1, ConnectorError> {
+        let oracle_proof_bytes = hex::decode(&payload.proof_bytes)?;
+        let mut index_set = IndexSet::new();
+        index_set.insert(self.badge_id.clone());
 
-pub async fn transaction_status(client: &Client, intent_hash: &str) -> TransactionStatus {
-    let mut map = HashMap::new();
-    map.insert("intent_hash", intent_hash);
-
-    let resp = client
-        .post(format!("{}/transaction/status", GATEWAY_URL))
-        .header(ACCEPT, "application/json")
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "oracle-pull-example")
-        .json(&map)
-        .send()
-        .await
-        .expect("Unable to fetch tx status");
-
-    resp.json().await.expect("Unable to parse tx status")
+        Ok(ManifestBuilder::new()
+            .lock_fee(self.account(), lock_fee)
+            .call_method(
+                DynamicGlobalAddress::from(self.component_address),
+                self.method.as_str(),
+                manifest_args!(oracle_proof_bytes, index_set),
+            )
+            .build())
+    }
+
+    /// Header valid from the current epoch, with a fresh nonce
+    async fn header(&self) -> Result<TransactionHeaderV1, ConnectorError> {
+        let epoch = self.gateway.current_epoch().await?;
+        Ok(TransactionHeaderV1 {
+            network_id: self.network.id,
+            start_epoch_inclusive: Epoch::of(epoch),
+            end_epoch_exclusive: Epoch::of(epoch + self.epoch_window),
+            nonce: self.next_nonce(),
+            notary_public_key: self.notary.public_key(),
+            notary_is_signatory: self.notary_is_signatory,
+            tip_percentage: self.tip_percentage,
+        })
+    }
+
+    async fn notarize(
+        &self,
+        manifest: TransactionManifestV1,
+    ) -> Result<(NotarizedTransactionV1, IntentHash), ConnectorError> {
+        create_notarized_transaction(self.header().await?, manifest, &self.signers, &self.notary)
+    }
 }
 
-pub async fn transaction_submit(client: &Client, tx_bytes_hex: String) -> TransactionSubmit {
-    let mut map = HashMap::new();
-    map.insert("notarized_transaction_hex", tx_bytes_hex);
-    let resp = client
-        .post(format!("{}/transaction/submit", GATEWAY_URL))
-        .header(ACCEPT, "application/json")
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "oracle-pull-example")
-        .json(&map)
-        .send()
-        .await
-        .expect("Unable to send the transaction");
-
-    resp.json()
-        .await
-        .expect("Unable to parse tx submit response")
+fn nonce_seed() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() as u32)
+        .unwrap_or_default()
 }
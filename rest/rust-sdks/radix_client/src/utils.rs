@@ -1,28 +1,31 @@
+use crate::errors::ConnectorError;
+use crate::keys::RadixKey;
 use transaction::prelude::*;
 
+/// Build a transaction from `header`, signed by every intent signer then notarized.
+/// The header carries the nonce and the epoch window, pick a fresh nonce for every transaction.
 pub fn create_notarized_transaction(
-    network_definition: &NetworkDefinition,
-    epoch: u64,
-    private_key: &Secp256k1PrivateKey,
+    header: TransactionHeaderV1,
     manifest: TransactionManifestV1,
-) -> (NotarizedTransactionV1, IntentHash) {
-    let transaction = TransactionBuilder::new()
-        .header(TransactionHeaderV1 {
-            // Below params are just for the test.
-            // They shall be adjusted with care and awareness.
-            // Eg. in production nonce mustn't be hardcoded.
-            network_id: network_definition.id,
-            start_epoch_inclusive: Epoch::of(epoch),
-            end_epoch_exclusive: Epoch::of(epoch + 10),
-            nonce: 5,
-            notary_public_key: private_key.public_key().into(),
-            notary_is_signatory: false,
-            tip_percentage: 0,
-        })
-        .manifest(manifest)
-        .notarize(private_key)
-        .build();
+    signers: &[RadixKey],
+    notary: &RadixKey,
+) -> Result<(NotarizedTransactionV1, IntentHash), ConnectorError> {
+    let mut builder = TransactionBuilder::new().header(header).manifest(manifest);
+    for signer in signers {
+        builder = match signer {
+            RadixKey::Ed25519(key) => builder.sign(key),
+            RadixKey::Secp256k1(key) => builder.sign(key),
+        };
+    }
+    let transaction = match notary {
+        RadixKey::Ed25519(key) => builder.notarize(key),
+        RadixKey::Secp256k1(key) => builder.notarize(key),
+    }
+    .build();
 
-    let intent_hash = transaction.prepare().unwrap().intent_hash();
-    (transaction, intent_hash)
-}
\ No newline at end of file
+    let intent_hash = transaction
+        .prepare()
+        .map_err(|e| ConnectorError::TransactionBuild(format!("{e:?}")))?
+        .intent_hash();
+    Ok((transaction, intent_hash))
+}
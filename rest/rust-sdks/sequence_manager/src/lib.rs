@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Hands out increasing sequence numbers for a single account, so that several transactions can be
+/// in flight at once without colliding. Clones share the same counter.
+#[derive(Clone, Default)]
+pub struct SequenceManager {
+    next: Arc<Mutex<Option<u64>>>,
+}
+
+impl SequenceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve the next sequence number, fetching it from chain with `fetch` if not synced yet.
+    /// A reserved number that ends up not being broadcast must be given back with [`Self::release`].
+    pub async fn reserve<E, F, Fut>(&self, fetch: F) -> Result<u64, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64, E>>,
+    {
+        let mut next = self.next.lock().await;
+        let sequence_number = match *next {
+            Some(sequence_number) => sequence_number,
+            None => fetch().await?,
+        };
+        *next = Some(sequence_number + 1);
+        Ok(sequence_number)
+    }
+
+    /// Give back a reserved number that was never broadcast. When later numbers were reserved in
+    /// the meantime the counter can not be rolled back, so it is resynced from chain instead.
+    pub async fn release(&self, sequence_number: u64) {
+        let mut next = self.next.lock().await;
+        *next = match *next {
+            Some(next) if next == sequence_number + 1 => Some(sequence_number),
+            _ => None,
+        };
+    }
+
+    /// Drop the local counter, the next reservation resyncs from chain
+    pub async fn reset(&self) {
+        *self.next.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn reserve(manager: &SequenceManager, on_chain: u64) -> u64 {
+        manager
+            .reserve(|| async { Ok::<_, ()>(on_chain) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reserves_increasing_numbers() {
+        let manager = SequenceManager::new();
+        assert_eq!(reserve(&manager, 5).await, 5);
+        assert_eq!(reserve(&manager, 0).await, 6);
+    }
+
+    #[tokio::test]
+    async fn release_rolls_back_the_last_reservation() {
+        let manager = SequenceManager::new();
+        let sequence_number = reserve(&manager, 5).await;
+        manager.release(sequence_number).await;
+        assert_eq!(reserve(&manager, 0).await, 5);
+    }
+
+    #[tokio::test]
+    async fn release_resyncs_when_later_numbers_are_reserved() {
+        let manager = SequenceManager::new();
+        let first = reserve(&manager, 5).await;
+        let _second = reserve(&manager, 0).await;
+        manager.release(first).await;
+        assert_eq!(reserve(&manager, 6).await, 6);
+    }
+}
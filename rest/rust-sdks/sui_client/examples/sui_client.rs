@@ -25,12 +25,15 @@ async fn main() {
 }
 
 async fn call_contract(input: PullResponseSui) {
-    let sui_connector = SuiConnector::new(SuiConfig::new(
-        "<--secret-key-->",
-        "<--rpc-url-->",
-        "<-contract-address-->",
-        300000000,
-    ))
+    let sui_connector = SuiConnector::new(
+        SuiConfig::new(
+            "<--secret-key-->",
+            "<--rpc-url-->",
+            "<-contract-address-->",
+            300000000,
+        )
+        .with_price_event_type("<--price-event-type-->"),
+    )
     .await
     .unwrap();
     invoke_sui_chain(input, sui_connector).await
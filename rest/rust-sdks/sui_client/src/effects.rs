@@ -0,0 +1,193 @@
+use crate::errors::ConnectorError;
+use crate::types::{PriceData, SuiSubmission};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use sui_sdk::rpc_types::{
+    SuiEvent, SuiExecutionStatus, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponse,
+};
+use sui_types::base_types::ObjectID;
+
+/// Abort raised by a move function, decoded from the execution failure status
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveAbort {
+    pub address: String,
+    pub module: String,
+    pub function: Option<String>,
+    pub code: u64,
+}
+
+impl fmt::Display for MoveAbort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.address, self.module)?;
+        if let Some(function) = &self.function {
+            write!(f, "::{}", function)?;
+        }
+        write!(f, " aborted with code {}", self.code)
+    }
+}
+
+/// Move type of the oracle events carrying the verified prices, `package::module::Name`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceEventType {
+    pub package: ObjectID,
+    pub module: String,
+    pub name: String,
+}
+
+impl PriceEventType {
+    /// Whether the event is of this type, whatever its type parameters
+    pub fn matches(&self, event: &SuiEvent) -> bool {
+        ObjectID::from(event.type_.address) == self.package
+            && event.type_.module.as_str() == self.module
+            && event.type_.name.as_str() == self.name
+    }
+}
+
+impl FromStr for PriceEventType {
+    type Err = ConnectorError;
+
+    fn from_str(event_type: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || ConnectorError::InvalidArgument(format!("invalid event type {event_type}"));
+        let mut parts = event_type.split("::");
+        let (Some(package), Some(module), Some(name), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            package: ObjectID::from_hex_literal(package).map_err(|_| invalid())?,
+            module: module.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Check the transaction effects and decode the oracle prices from the events of `price_event`.
+/// The transaction is committed once its effects are successful, so a decoding failure is
+/// reported in `prices` rather than as an error, the digest must not be lost.
+pub fn submission(
+    response: SuiTransactionBlockResponse,
+    price_event: Option<&PriceEventType>,
+) -> Result<SuiSubmission, ConnectorError> {
+    let effects = response
+        .effects
+        .ok_or_else(|| ConnectorError::SuiTransaction("no effects in response".to_string()))?;
+    if let SuiExecutionStatus::Failure { error } = effects.status() {
+        return Err(execution_error(error, &effects));
+    }
+    let events = response
+        .events
+        .map(|events| events.data)
+        .unwrap_or_default();
+    let prices = match price_event {
+        Some(price_event) => decode_price_data(&events, price_event).map_err(|e| {
+            log::warn!("sui: unable to decode prices of {}: {e}", response.digest);
+            e.to_string()
+        }),
+        None => Ok(vec![]),
+    };
+    Ok(SuiSubmission {
+        digest: response.digest,
+        checkpoint: response.checkpoint,
+        gas_used: effects.gas_cost_summary().clone(),
+        events,
+        prices,
+    })
+}
+
+/// Map a failure status to a structured move abort when it is one. The digest and gas used are
+/// kept, a failed transaction is still committed and charged for gas.
+pub fn execution_error(error: &str, effects: &SuiTransactionBlockEffects) -> ConnectorError {
+    let digest = *effects.transaction_digest();
+    let gas_used = effects.gas_cost_summary().clone();
+    match parse_move_abort(error) {
+        Some(abort) => ConnectorError::MoveAbort {
+            abort,
+            digest,
+            gas_used,
+        },
+        None => ConnectorError::ExecutionFailed {
+            error: error.to_string(),
+            digest,
+            gas_used,
+        },
+    }
+}
+
+/// Parse statuses like
+/// `MoveAbort(MoveLocation { module: ModuleId { address: 0x.., name: Identifier("m") }, function: 1,
+/// instruction: 2, function_name: Some("f") }, 3) in command 0`
+pub fn parse_move_abort(error: &str) -> Option<MoveAbort> {
+    let location = error.strip_prefix("MoveAbort(")?;
+    let address = between(location, "address: ", ",")?;
+    let module = between(location, "name: Identifier(\"", "\")")?;
+    let function = between(location, "function_name: Some(\"", "\")").map(str::to_string);
+    let code = between(&location[location.rfind("}, ")?..], "}, ", ")")?;
+    Some(MoveAbort {
+        address: address.to_string(),
+        module: module.to_string(),
+        function,
+        code: code.trim().parse().ok()?,
+    })
+}
+
+fn between<'a>(value: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let value = &value[value.find(start)? + start.len()..];
+    Some(&value[..value.find(end)?])
+}
+
+/// Oracle prices carried by the events of `price_event`, either as the event itself or as a list
+/// field of it. Other events are ignored.
+pub fn decode_price_data(
+    events: &[SuiEvent],
+    price_event: &PriceEventType,
+) -> Result<Vec<PriceData>, ConnectorError> {
+    let mut prices = vec![];
+    for event in events.iter().filter(|event| price_event.matches(event)) {
+        let Value::Object(fields) = &event.parsed_json else {
+            continue;
+        };
+        if fields.contains_key("pair_index") {
+            prices.push(price_data(&event.parsed_json)?);
+            continue;
+        }
+        for value in fields.values() {
+            if let Value::Array(items) = value {
+                for item in items.iter().filter(|item| item.get("pair_index").is_some()) {
+                    prices.push(price_data(item)?);
+                }
+            }
+        }
+    }
+    Ok(prices)
+}
+
+fn price_data(value: &Value) -> Result<PriceData, ConnectorError> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .ok_or_else(|| ConnectorError::EventDecode(format!("missing {name} in {value}")))
+    };
+    Ok(PriceData {
+        pair_index: decode_integer(field("pair_index")?)?,
+        value: decode_integer(field("value")?)?,
+        decimal: decode_integer(field("decimal")?)?,
+        timestamp: decode_integer(field("timestamp")?)?,
+        round: decode_integer(field("round")?)?,
+    })
+}
+
+/// Move integers above 32 bits are json strings
+fn decode_integer<T: TryFrom<u128>>(value: &Value) -> Result<T, ConnectorError> {
+    let integer = match value {
+        Value::String(v) => v.parse::<u128>().ok(),
+        Value::Number(v) => v.as_u64().map(u128::from),
+        _ => None,
+    };
+    integer
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or_else(|| ConnectorError::EventDecode(format!("invalid move integer: {value}")))
+}
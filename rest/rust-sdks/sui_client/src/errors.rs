@@ -1,3 +1,6 @@
+use crate::effects::MoveAbort;
+use sui_types::digests::TransactionDigest;
+use sui_types::gas::GasCostSummary;
 use thiserror::Error;
 
 #[allow(missing_docs)]
@@ -9,4 +12,46 @@ pub enum ConnectorError {
     SuiTransaction(String),
     #[error("invalid secret key")]
     InvalidSecretKey,
+    #[error("unable to load keystore: {0}")]
+    Keystore(String),
+    #[error("no key with alias {0} in keystore")]
+    UnknownAlias(String),
+    #[error("invalid move call argument: {0}")]
+    InvalidArgument(String),
+    #[error(transparent)]
+    FromHex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    Rpc(#[from] sui_sdk::error::Error),
+    #[error("object not found: {0}")]
+    ObjectNotFound(String),
+    #[error("unable to build transaction: {0}")]
+    TransactionBuild(String),
+    #[error("dry run failed: {0}")]
+    DryRun(String),
+    #[error("gas budget exceeded, dry run requires {required} but budget is {budget}")]
+    GasBudgetExceeded { required: u64, budget: u64 },
+    #[error("no gas coin covering the gas budget")]
+    NoGasCoins,
+    #[error("object {0} is not a gas coin")]
+    InvalidGasCoin(String),
+    #[error("insufficient gas balance {balance} to split {count} gas coins of {budget}")]
+    InsufficientGas {
+        balance: u64,
+        count: usize,
+        budget: u64,
+    },
+    #[error("{abort} in transaction {digest}")]
+    MoveAbort {
+        abort: MoveAbort,
+        digest: TransactionDigest,
+        gas_used: GasCostSummary,
+    },
+    #[error("transaction {digest} execution failed: {error}")]
+    ExecutionFailed {
+        error: String,
+        digest: TransactionDigest,
+        gas_used: GasCostSummary,
+    },
+    #[error("unable to decode event: {0}")]
+    EventDecode(String),
 }
@@ -0,0 +1,105 @@
+use crate::errors::ConnectorError;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use sui_types::base_types::ObjectID;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Pool of gas coins. Each in-flight transaction leases its own coin so concurrent submissions
+/// do not fight over the same coin.
+pub struct GasCoinPool {
+    coins: Arc<Mutex<PoolCoins>>,
+    available: Arc<Semaphore>,
+}
+
+#[derive(Default)]
+struct PoolCoins {
+    free: VecDeque<ObjectID>,
+    leased: HashSet<ObjectID>,
+}
+
+impl GasCoinPool {
+    pub fn new(coins: Vec<ObjectID>) -> Result<Self, ConnectorError> {
+        if coins.is_empty() {
+            return Err(ConnectorError::NoGasCoins);
+        }
+        let pool = Self {
+            coins: Arc::new(Mutex::new(PoolCoins::default())),
+            available: Arc::new(Semaphore::new(0)),
+        };
+        pool.add(coins);
+        Ok(pool)
+    }
+
+    /// Wait for a free coin, it goes back to the pool when the lease is dropped
+    pub async fn acquire(&self) -> Result<GasCoinLease, ConnectorError> {
+        let permit = self
+            .available
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ConnectorError::NoGasCoins)?;
+        let mut coins = self.coins.lock().expect("gas coin pool lock poisoned");
+        let coin = coins.free.pop_front().ok_or(ConnectorError::NoGasCoins)?;
+        coins.leased.insert(coin);
+        Ok(GasCoinLease {
+            coin,
+            coins: self.coins.clone(),
+            permit: Some(permit),
+        })
+    }
+
+    /// Add coins the pool does not hold yet
+    pub fn add(&self, new_coins: Vec<ObjectID>) {
+        let mut coins = self.coins.lock().expect("gas coin pool lock poisoned");
+        for coin in new_coins {
+            if !coins.leased.contains(&coin) && !coins.free.contains(&coin) {
+                coins.free.push_back(coin);
+                self.available.add_permits(1);
+            }
+        }
+    }
+
+    /// Coins held by the pool, free or leased
+    pub fn coin_ids(&self) -> HashSet<ObjectID> {
+        let coins = self.coins.lock().expect("gas coin pool lock poisoned");
+        coins.free.iter().chain(&coins.leased).copied().collect()
+    }
+
+    /// Number of coins held by the pool, free or leased
+    pub fn size(&self) -> usize {
+        let coins = self.coins.lock().expect("gas coin pool lock poisoned");
+        coins.free.len() + coins.leased.len()
+    }
+}
+
+/// Gas coin reserved for a single transaction
+pub struct GasCoinLease {
+    coin: ObjectID,
+    coins: Arc<Mutex<PoolCoins>>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl GasCoinLease {
+    pub fn coin(&self) -> ObjectID {
+        self.coin
+    }
+
+    /// Take the coin out of the pool for good, e.g. once it no longer covers the gas budget
+    pub fn retire(mut self) {
+        if let Some(permit) = self.permit.take() {
+            permit.forget();
+        }
+    }
+}
+
+impl Drop for GasCoinLease {
+    fn drop(&mut self) {
+        // The permit is released after this runs, so the coin is back before anyone can wait on it
+        if let Ok(mut coins) = self.coins.lock() {
+            coins.leased.remove(&self.coin);
+            if self.permit.is_some() {
+                coins.free.push_back(self.coin);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod effects;
 pub mod errors;
+pub mod gas_pool;
+pub mod ptb;
 pub mod sui_connector;
 pub mod types;
 
@@ -0,0 +1,167 @@
+use crate::errors::ConnectorError;
+use std::collections::HashMap;
+use sui_types::base_types::ObjectID;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{Argument, CallArg, ObjectArg, ProgrammableTransaction};
+use sui_types::{Identifier, TypeTag, SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION};
+
+/// Move function called by a programmable transaction block
+#[derive(Clone, Debug)]
+pub struct MoveCallSpec {
+    pub package: ObjectID,
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<TypeTag>,
+}
+
+impl MoveCallSpec {
+    pub fn new(package: ObjectID, module: &str, function: &str) -> Self {
+        Self {
+            package,
+            module: module.to_string(),
+            function: function.to_string(),
+            type_arguments: vec![],
+        }
+    }
+
+    pub fn with_type_arguments(mut self, type_arguments: Vec<TypeTag>) -> Self {
+        self.type_arguments = type_arguments;
+        self
+    }
+}
+
+/// Argument of a move call following the proof verification
+#[derive(Clone, Debug)]
+pub enum PtbArg {
+    /// Price data returned by the proof verification call
+    VerifiedPriceData,
+    /// Value returned by a previous follow-up call, by index
+    CallResult(usize),
+    /// Object resolved from chain, `mutable` must match the callee parameter: `false` for `&T`,
+    /// `true` for `&mut T` or `T`
+    Object { id: ObjectID, mutable: bool },
+    /// Bcs encoded pure value
+    Pure(Vec<u8>),
+    /// The `0x6` clock
+    Clock,
+}
+
+/// Objects and proof consumed by the proof verification call
+pub struct VerifyInputs {
+    pub dkg: ObjectArg,
+    pub oracle_holder: ObjectArg,
+    pub merkle_root: ObjectArg,
+    pub proof_bytes: Vec<u8>,
+}
+
+/// Builds a programmable transaction block verifying an oracle proof, then calling user move
+/// functions on the returned price data in the same transaction
+#[derive(Clone, Debug)]
+pub struct ProofTransactionBuilder {
+    verify: MoveCallSpec,
+    calls: Vec<(MoveCallSpec, Vec<PtbArg>)>,
+}
+
+impl ProofTransactionBuilder {
+    /// `verify` is called with the dkg, oracle holder, merkle root, clock and proof bytes
+    pub fn new(verify: MoveCallSpec) -> Self {
+        Self {
+            verify,
+            calls: vec![],
+        }
+    }
+
+    /// Append a move call run after the proof verification
+    pub fn then_call(mut self, call: MoveCallSpec, args: Vec<PtbArg>) -> Self {
+        self.calls.push((call, args));
+        self
+    }
+
+    /// Move call verifying the proof
+    pub fn verify_call(&self) -> &MoveCallSpec {
+        &self.verify
+    }
+
+    /// Objects used by the follow-up calls with the call and position they are passed at, to be
+    /// checked against the callee signature and resolved from chain
+    pub fn object_args(&self) -> Vec<(&MoveCallSpec, usize, ObjectID, bool)> {
+        self.calls
+            .iter()
+            .flat_map(|(call, args)| {
+                args.iter()
+                    .enumerate()
+                    .filter_map(move |(position, arg)| match arg {
+                        PtbArg::Object { id, mutable } => Some((call, position, *id, *mutable)),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    pub fn build(
+        &self,
+        inputs: VerifyInputs,
+        objects: &HashMap<ObjectID, ObjectArg>,
+    ) -> Result<ProgrammableTransaction, ConnectorError> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let verify_args = vec![
+            ptb.obj(inputs.dkg).map_err(build_error)?,
+            ptb.obj(inputs.oracle_holder).map_err(build_error)?,
+            ptb.obj(inputs.merkle_root).map_err(build_error)?,
+            ptb.obj(clock()).map_err(build_error)?,
+            ptb.pure(inputs.proof_bytes).map_err(build_error)?,
+        ];
+        let price_data = move_call(&mut ptb, &self.verify, verify_args)?;
+
+        let mut results = vec![];
+        for (call, args) in &self.calls {
+            let args = args
+                .iter()
+                .map(|arg| match arg {
+                    PtbArg::VerifiedPriceData => Ok(price_data),
+                    PtbArg::CallResult(idx) => results.get(*idx).copied().ok_or_else(|| {
+                        ConnectorError::TransactionBuild(format!("no call result at index {idx}"))
+                    }),
+                    PtbArg::Object { id, .. } => {
+                        let object = objects.get(id).ok_or_else(|| {
+                            ConnectorError::TransactionBuild(format!("unresolved object {id}"))
+                        })?;
+                        ptb.obj(*object).map_err(build_error)
+                    }
+                    PtbArg::Pure(bytes) => {
+                        ptb.input(CallArg::Pure(bytes.clone())).map_err(build_error)
+                    }
+                    PtbArg::Clock => ptb.obj(clock()).map_err(build_error),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            results.push(move_call(&mut ptb, call, args)?);
+        }
+        Ok(ptb.finish())
+    }
+}
+
+fn move_call(
+    ptb: &mut ProgrammableTransactionBuilder,
+    call: &MoveCallSpec,
+    args: Vec<Argument>,
+) -> Result<Argument, ConnectorError> {
+    Ok(ptb.programmable_move_call(
+        call.package,
+        Identifier::new(call.module.as_str()).map_err(build_error)?,
+        Identifier::new(call.function.as_str()).map_err(build_error)?,
+        call.type_arguments.clone(),
+        args,
+    ))
+}
+
+fn clock() -> ObjectArg {
+    ObjectArg::SharedObject {
+        id: SUI_CLOCK_OBJECT_ID,
+        initial_shared_version: SUI_CLOCK_OBJECT_SHARED_VERSION,
+        mutable: false,
+    }
+}
+
+fn build_error(e: impl std::fmt::Display) -> ConnectorError {
+    ConnectorError::TransactionBuild(e.to_string())
+}
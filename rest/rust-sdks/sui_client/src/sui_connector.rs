@@ -1,54 +1,52 @@
+use crate::effects::{execution_error, submission, PriceEventType};
 use crate::errors::ConnectorError;
-use crate::types::PullResponseSui;
+use crate::gas_pool::{GasCoinLease, GasCoinPool};
+use crate::ptb::{MoveCallSpec, ProofTransactionBuilder, VerifyInputs};
+use crate::types::{PullResponseSui, SuiSubmission};
 use shared_crypto::intent::Intent;
-use std::str::FromStr;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{fmt::Display, future::Future};
-use sui_keys::keystore::{AccountKeystore, InMemKeystore};
-use sui_sdk::json::SuiJsonValue;
-use sui_sdk::rpc_types::{SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore};
+use sui_sdk::rpc_types::{
+    Coin, DryRunTransactionBlockResponse, SuiExecutionStatus, SuiMoveNormalizedType,
+    SuiObjectDataOptions, SuiRawData, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions,
+};
 use sui_sdk::{SuiClient, SuiClientBuilder};
-use sui_types::base_types::ObjectID;
 use sui_types::base_types::SuiAddress;
+use sui_types::base_types::{ObjectID, ObjectRef};
 use sui_types::crypto::EncodeDecodeBase64;
-use sui_types::crypto::SuiKeyPair;
-use sui_types::transaction::{Transaction, TransactionData};
+use sui_types::crypto::{SuiKeyPair, SUI_PRIV_KEY_PREFIX};
+use sui_types::gas_coin::GasCoin;
+use sui_types::object::Owner;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_types::transaction::{
+    Argument, ObjectArg, ProgrammableTransaction, Transaction, TransactionData, TransactionDataAPI,
+};
+use tokio::sync::{Mutex, OnceCell};
 
 const MODULE: &str = "<CONTRACT MODULE>"; // Module name of your contract. Ex. pull_example
 const ENTRY: &str = "<CONTRACT FUNCTION>"; // Module function name of your contract. Ex. get_pair_price
-const CLOCK: &str = "0x6";
+const DRY_RUN_GAS_MARGIN_PERCENT: u64 = 20; // Headroom added on top of the dry-run gas cost
+const MAX_GAS_PAYMENT_COINS: usize = 256; // Protocol limit of coins merged into one gas payment
 pub async fn invoke_sui_chain(payload: PullResponseSui, sui_connector: SuiConnector) {
-    let sui_arg = vec![
-        SuiJsonValue::from_str(&payload.dkg_object).unwrap(),
-        SuiJsonValue::from_str(&payload.oracle_holder_object).unwrap(),
-        SuiJsonValue::from_str(&payload.merkle_root_object).unwrap(),
-        SuiJsonValue::from_str(CLOCK).unwrap(),
-        SuiJsonValue::from_bcs_bytes(None, &hex::decode(payload.proof_bytes).unwrap()).unwrap(),
-    ];
-    let tx_data = sui_connector
-        .client
-        .with_sui(|sui_client| {
-            sui_client.transaction_builder().move_call(
-                sui_connector.get_sui_address().unwrap(),
-                ObjectID::from_hex_literal(&sui_connector.sc_addr).unwrap(),
-                MODULE,
-                ENTRY,
-                vec![],
-                sui_arg.clone(),
-                None,
-                sui_connector.gas_budget,
-                None,
-            )
-        })
-        .await
-        .unwrap();
-    let transaction = sui_connector.sign_and_execute_tx(tx_data).await;
-    if let Ok(transaction) = transaction {
-        let transaction = transaction.digest.to_string();
-        println!("{}", transaction);
-    } else {
-        println!("{:?}", transaction);
+    match sui_connector.submit_proof(&payload).await {
+        Ok(submission) => {
+            println!("{}", submission.digest);
+            match submission.prices {
+                Ok(prices) => {
+                    for price in prices {
+                        println!("{:?}", price);
+                    }
+                }
+                Err(err) => println!("unable to decode prices: {err}"),
+            }
+        }
+        Err(err) => println!("{:?}", err),
     }
 }
 
@@ -57,9 +55,15 @@ pub struct SuiConfig<'a> {
     client_url: &'a str,
     sc_address: &'a str,
     gas_budget: u64,
+    sponsor_key: Option<&'a str>,
+    gas_coin_count: usize,
+    keystore_path: Option<&'a str>,
+    price_event_type: Option<&'a str>,
 }
 
 impl<'a> SuiConfig<'a> {
+    /// `secret_key` is a bech32 `suiprivkey` or a base64 `flag || private key`,
+    /// any of the Ed25519, Secp256k1 and Secp256r1 schemes
     pub fn new(
         secret_key: &'a str,
         client_url: &'a str,
@@ -71,15 +75,51 @@ impl<'a> SuiConfig<'a> {
             client_url,
             sc_address,
             gas_budget,
+            sponsor_key: None,
+            gas_coin_count: 1,
+            keystore_path: None,
+            price_event_type: None,
         }
     }
+
+    /// Load the keys from a `sui.keystore` file, the secret key and the sponsor key are then
+    /// aliases of that keystore
+    pub fn with_keystore(mut self, keystore_path: &'a str) -> Self {
+        self.keystore_path = Some(keystore_path);
+        self
+    }
+
+    /// Pay gas from the sponsor account, the transaction is signed by both the sender and the sponsor
+    pub fn with_sponsor_key(mut self, sponsor_key: &'a str) -> Self {
+        self.sponsor_key = Some(sponsor_key);
+        self
+    }
+
+    /// Move type of the oracle events carrying the verified prices, e.g. `0x..::module::Event`.
+    /// Only those events are decoded into the submission's prices.
+    pub fn with_price_event_type(mut self, price_event_type: &'a str) -> Self {
+        self.price_event_type = Some(price_event_type);
+        self
+    }
+
+    /// Number of gas coins kept for concurrent submissions, coins are split or merged to match
+    pub fn with_gas_coin_count(mut self, gas_coin_count: usize) -> Self {
+        self.gas_coin_count = gas_coin_count.max(1);
+        self
+    }
 }
 
 pub struct SuiConnector {
     client: ClientWrapper,
     sc_addr: String,
-    secret_key: String,
+    key_store: InMemKeystore,
+    sender: SuiAddress,
+    sponsor: Option<SuiAddress>,
     gas_budget: u64,
+    gas_coin_count: usize,
+    gas_pool: OnceCell<GasCoinPool>,
+    gas_pool_refill: Mutex<()>,
+    price_event: Option<PriceEventType>,
 }
 
 #[derive(Clone)]
@@ -97,58 +137,508 @@ impl SuiConnector {
             .await
             .map_err(|_| ConnectorError::InvalidUrl)?;
 
+        let keystore = conf
+            .keystore_path
+            .map(|path| {
+                FileBasedKeystore::new(&PathBuf::from(path))
+                    .map_err(|e| ConnectorError::Keystore(e.to_string()))
+            })
+            .transpose()?;
+        // Only the selected keys are kept, in memory, for the life of the connector
+        let mut key_store = InMemKeystore::default();
+        let sender = add_key(&mut key_store, keystore.as_ref(), conf.secret_key)?;
+        let sponsor = conf
+            .sponsor_key
+            .map(|sponsor_key| add_key(&mut key_store, keystore.as_ref(), sponsor_key))
+            .transpose()?;
+
         let sui_connector = Self {
             client: ClientWrapper::new(client, None),
             sc_addr: conf.sc_address.to_string(),
-            secret_key: conf.secret_key.to_string(),
+            key_store,
+            sender,
+            sponsor,
             gas_budget: conf.gas_budget,
+            gas_coin_count: conf.gas_coin_count,
+            gas_pool: OnceCell::new(),
+            gas_pool_refill: Mutex::new(()),
+            price_event: conf
+                .price_event_type
+                .map(str::parse::<PriceEventType>)
+                .transpose()?,
         };
         Ok(sui_connector)
     }
 
+    /// Verify the proof on chain, with a gas budget sized by a dry run
+    pub async fn submit_proof(
+        &self,
+        payload: &PullResponseSui,
+    ) -> Result<SuiSubmission, ConnectorError> {
+        let builder = ProofTransactionBuilder::new(self.verify_call()?);
+        self.submit_proof_with(payload, &builder).await
+    }
+
+    /// Verify the proof and run the builder's follow-up move calls in the same transaction
+    pub async fn submit_proof_with(
+        &self,
+        payload: &PullResponseSui,
+        builder: &ProofTransactionBuilder,
+    ) -> Result<SuiSubmission, ConnectorError> {
+        let pt = self.build_proof_transaction(payload, builder).await?;
+        // The lease keeps the coin out of the pool until the transaction is executed
+        let (_lease, gas_coin) = self.lease_gas_coin().await?;
+        let tx_data = self
+            .programmable_tx_data(pt.clone(), gas_coin, self.gas_budget)
+            .await?;
+        let gas_budget = self.estimate_gas_budget(tx_data).await?;
+        let tx_data = self.programmable_tx_data(pt, gas_coin, gas_budget).await?;
+        submission(
+            self.sign_and_execute_tx(tx_data).await?,
+            self.price_event.as_ref(),
+        )
+    }
+
+    /// Move call verifying the proof, `MODULE::ENTRY` of the configured contract
+    pub fn verify_call(&self) -> Result<MoveCallSpec, ConnectorError> {
+        let package = ObjectID::from_hex_literal(&self.sc_addr)
+            .map_err(|e| ConnectorError::InvalidArgument(e.to_string()))?;
+        Ok(MoveCallSpec::new(package, MODULE, ENTRY))
+    }
+
+    /// Resolve the objects used by the proof transaction and build it. Shared objects are passed
+    /// mutably only when the callee takes them by `&mut` or by value.
+    pub async fn build_proof_transaction(
+        &self,
+        payload: &PullResponseSui,
+        builder: &ProofTransactionBuilder,
+    ) -> Result<ProgrammableTransaction, ConnectorError> {
+        let verify = builder.verify_call();
+        let verify_parameters = self.mutable_parameters(verify).await?;
+        let mutable = |position| parameter_mutability(&verify_parameters, verify, position);
+        let inputs = VerifyInputs {
+            dkg: self
+                .object_arg(object_id(&payload.dkg_object)?, mutable(0)?)
+                .await?,
+            oracle_holder: self
+                .object_arg(object_id(&payload.oracle_holder_object)?, mutable(1)?)
+                .await?,
+            merkle_root: self
+                .object_arg(object_id(&payload.merkle_root_object)?, mutable(2)?)
+                .await?,
+            proof_bytes: hex::decode(&payload.proof_bytes)?,
+        };
+
+        let mut signatures = HashMap::new();
+        let mut object_mutability: HashMap<ObjectID, bool> = HashMap::new();
+        for (call, position, id, mutable) in builder.object_args() {
+            let key = (call.package, call.module.clone(), call.function.clone());
+            if !signatures.contains_key(&key) {
+                signatures.insert(key.clone(), self.mutable_parameters(call).await?);
+            }
+            if parameter_mutability(&signatures[&key], call, position)? != mutable {
+                return Err(ConnectorError::InvalidArgument(format!(
+                    "object {id} passed with mutable: {mutable} does not match parameter \
+                     {position} of {}::{}",
+                    call.module, call.function
+                )));
+            }
+            *object_mutability.entry(id).or_default() |= mutable;
+        }
+        let mut objects = HashMap::new();
+        for (id, mutable) in object_mutability {
+            objects.insert(id, self.object_arg(id, mutable).await?);
+        }
+        builder.build(inputs, &objects)
+    }
+
+    /// Whether each parameter of the move function needs a mutable object, i.e. is not taken by
+    /// immutable reference
+    pub async fn mutable_parameters(
+        &self,
+        call: &MoveCallSpec,
+    ) -> Result<Vec<bool>, ConnectorError> {
+        let function = self
+            .client
+            .with_sui(|sui| {
+                sui.read_api().get_normalized_move_function(
+                    call.package,
+                    call.module.clone(),
+                    call.function.clone(),
+                )
+            })
+            .await?;
+        Ok(function
+            .parameters
+            .iter()
+            .map(|parameter| !matches!(parameter, SuiMoveNormalizedType::Reference(_)))
+            .collect())
+    }
+
+    /// Look the object up on chain to pass it by reference, shared objects with their initial version
+    pub async fn object_arg(
+        &self,
+        id: ObjectID,
+        mutable: bool,
+    ) -> Result<ObjectArg, ConnectorError> {
+        let response = self
+            .client
+            .with_sui(|sui| {
+                sui.read_api()
+                    .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            })
+            .await?;
+        let data = response
+            .data
+            .ok_or_else(|| ConnectorError::ObjectNotFound(id.to_string()))?;
+        match data.owner {
+            Some(Owner::Shared {
+                initial_shared_version,
+            }) => Ok(ObjectArg::SharedObject {
+                id,
+                initial_shared_version,
+                mutable,
+            }),
+            _ => Ok(ObjectArg::ImmOrOwnedObject(data.object_ref())),
+        }
+    }
+
+    async fn programmable_tx_data(
+        &self,
+        pt: ProgrammableTransaction,
+        gas_coin: ObjectRef,
+        gas_budget: u64,
+    ) -> Result<TransactionData, ConnectorError> {
+        let sender = self.get_sui_address()?;
+        let gas_price = self.reference_gas_price().await?;
+        Ok(match self.get_sponsor_address()? {
+            Some(sponsor) => TransactionData::new_programmable_allow_sponsor(
+                sender,
+                vec![gas_coin],
+                pt,
+                gas_budget,
+                gas_price,
+                sponsor,
+            ),
+            None => {
+                TransactionData::new_programmable(sender, vec![gas_coin], pt, gas_budget, gas_price)
+            }
+        })
+    }
+
+    async fn reference_gas_price(&self) -> Result<u64, ConnectorError> {
+        Ok(self
+            .client
+            .with_sui(|sui| sui.read_api().get_reference_gas_price())
+            .await?)
+    }
+
+    /// Pool of gas coins owned by the gas payer, prepared on first use
+    pub async fn gas_pool(&self) -> Result<&GasCoinPool, ConnectorError> {
+        self.gas_pool
+            .get_or_try_init(|| async {
+                GasCoinPool::new(self.prepare_gas_coins(self.gas_coin_count).await?)
+            })
+            .await
+    }
+
+    /// Lease a coin of the pool covering the gas budget. Coins below the budget are retired and the
+    /// pool is refilled once it holds fewer than the configured gas coin count.
+    async fn lease_gas_coin(&self) -> Result<(GasCoinLease, ObjectRef), ConnectorError> {
+        let pool = self.gas_pool().await?;
+        loop {
+            let lease = pool.acquire().await?;
+            match self.gas_coin(lease.coin()).await {
+                Ok((gas_coin, balance)) if balance >= self.gas_budget => {
+                    return Ok((lease, gas_coin))
+                }
+                Ok((_, balance)) => log::debug!(
+                    "sui: gas coin {} balance {balance} is below the gas budget, retiring it",
+                    lease.coin()
+                ),
+                // Deleted, e.g. merged away, or no longer a gas coin
+                Err(
+                    e @ (ConnectorError::ObjectNotFound(_) | ConnectorError::InvalidGasCoin(_)),
+                ) => {
+                    log::debug!(
+                        "sui: gas coin {} is unusable, retiring it: {e}",
+                        lease.coin()
+                    )
+                }
+                Err(e) => return Err(e),
+            }
+            lease.retire();
+            if pool.size() < self.gas_coin_count {
+                match self.refill_gas_pool(pool).await {
+                    Err(e) if pool.size() == 0 => return Err(e),
+                    Err(e) => log::warn!("sui: unable to refill the gas coin pool: {e}"),
+                    Ok(()) => {}
+                }
+            }
+        }
+    }
+
+    /// Top the pool back up to the configured gas coin count with coins it does not hold yet.
+    /// Coins held by the pool are left alone, as leased ones may be paying for in-flight transactions.
+    async fn refill_gas_pool(&self, pool: &GasCoinPool) -> Result<(), ConnectorError> {
+        let _refill = self.gas_pool_refill.lock().await;
+        let missing = self.gas_coin_count.saturating_sub(pool.size());
+        if missing == 0 {
+            return Ok(());
+        }
+        let owner = self.gas_owner()?;
+        let held = pool.coin_ids();
+        let unheld = |coins: Vec<Coin>| -> Vec<Coin> {
+            coins
+                .into_iter()
+                .filter(|coin| !held.contains(&coin.coin_object_id))
+                .collect()
+        };
+        let coins = unheld(self.gas_coins(owner).await?);
+        let mut usable = self.usable_gas_coins(&coins);
+        if usable.len() < missing {
+            self.rebalance_gas_coins(owner, coins, missing).await?;
+            usable = self.usable_gas_coins(&unheld(self.gas_coins(owner).await?));
+        }
+        log::debug!("sui: adding {} gas coins to the pool", usable.len());
+        pool.add(usable);
+        Ok(())
+    }
+
+    /// Make sure the gas payer owns at least `count` coins covering the gas budget.
+    /// When it does not, all its coins are merged into one and split back into `count` coins.
+    pub async fn prepare_gas_coins(&self, count: usize) -> Result<Vec<ObjectID>, ConnectorError> {
+        let owner = self.gas_owner()?;
+        let coins = self.gas_coins(owner).await?;
+        let usable = self.usable_gas_coins(&coins);
+        if usable.len() >= count {
+            return Ok(usable);
+        }
+        self.rebalance_gas_coins(owner, coins, count).await?;
+        Ok(self.usable_gas_coins(&self.gas_coins(owner).await?))
+    }
+
+    fn usable_gas_coins(&self, coins: &[Coin]) -> Vec<ObjectID> {
+        coins
+            .iter()
+            .filter(|coin| coin.balance >= self.gas_budget)
+            .map(|coin| coin.coin_object_id)
+            .collect()
+    }
+
+    /// Every SUI coin owned by `owner`, largest first
+    async fn gas_coins(&self, owner: SuiAddress) -> Result<Vec<Coin>, ConnectorError> {
+        let mut coins = vec![];
+        let mut cursor = None;
+        loop {
+            let page = self
+                .client
+                .with_sui(|sui| sui.coin_read_api().get_coins(owner, None, cursor, None))
+                .await?;
+            coins.extend(page.data);
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        coins.sort_by(|a, b| b.balance.cmp(&a.balance));
+        Ok(coins)
+    }
+
+    /// Merge the coins through the gas payment and split `count - 1` new coins off the merged one
+    async fn rebalance_gas_coins(
+        &self,
+        owner: SuiAddress,
+        mut coins: Vec<Coin>,
+        count: usize,
+    ) -> Result<(), ConnectorError> {
+        coins.truncate(MAX_GAS_PAYMENT_COINS);
+        let balance: u64 = coins.iter().map(|coin| coin.balance).sum();
+        let share = balance.saturating_sub(self.gas_budget) / count as u64;
+        if share < self.gas_budget {
+            return Err(ConnectorError::InsufficientGas {
+                balance,
+                count,
+                budget: self.gas_budget,
+            });
+        }
+        log::debug!("sui: splitting {balance} into {count} gas coins of {share}");
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        if count > 1 {
+            ptb.pay_sui(vec![owner; count - 1], vec![share; count - 1])
+                .map_err(|e| ConnectorError::TransactionBuild(e.to_string()))?;
+        } else {
+            // Nothing to split, only merge the coins by sending the gas coin back to its owner
+            ptb.transfer_arg(owner, Argument::GasCoin);
+        }
+        let tx_data = TransactionData::new_programmable(
+            owner,
+            coins.iter().map(Coin::object_ref).collect(),
+            ptb.finish(),
+            self.gas_budget,
+            self.reference_gas_price().await?,
+        );
+        submission(self.sign_and_execute_tx(tx_data).await?, None)?;
+        Ok(())
+    }
+
+    /// Latest reference and balance of a gas coin, its version changes with every transaction it
+    /// pays for
+    async fn gas_coin(&self, id: ObjectID) -> Result<(ObjectRef, u64), ConnectorError> {
+        let response = self
+            .client
+            .with_sui(|sui| {
+                sui.read_api()
+                    .get_object_with_options(id, SuiObjectDataOptions::new().with_bcs())
+            })
+            .await?;
+        let data = response
+            .data
+            .ok_or_else(|| ConnectorError::ObjectNotFound(id.to_string()))?;
+        let Some(SuiRawData::MoveObject(object)) = &data.bcs else {
+            return Err(ConnectorError::InvalidGasCoin(id.to_string()));
+        };
+        let coin: GasCoin = object
+            .deserialize()
+            .map_err(|_| ConnectorError::InvalidGasCoin(id.to_string()))?;
+        Ok((data.object_ref(), coin.value()))
+    }
+
+    /// Execute the transaction against the node's current state without committing it
+    pub async fn dry_run(
+        &self,
+        tx_data: TransactionData,
+    ) -> Result<DryRunTransactionBlockResponse, ConnectorError> {
+        let response = self
+            .client
+            .with_sui(|sui| sui.read_api().dry_run_transaction_block(tx_data.clone()))
+            .await?;
+        if let SuiExecutionStatus::Failure { error } = response.effects.status() {
+            return Err(match execution_error(error, &response.effects) {
+                ConnectorError::ExecutionFailed { error, .. } => ConnectorError::DryRun(error),
+                // The digest is of the transaction that would have been executed
+                abort => abort,
+            });
+        }
+        Ok(response)
+    }
+
+    /// Gas budget covering the dry-run computation and storage cost plus a safety margin.
+    /// The configured gas budget is the upper bound.
+    pub async fn estimate_gas_budget(
+        &self,
+        tx_data: TransactionData,
+    ) -> Result<u64, ConnectorError> {
+        let response = self.dry_run(tx_data).await?;
+        let cost = response.effects.gas_cost_summary();
+        let required = cost.computation_cost + cost.storage_cost;
+        let gas_budget = required + required * DRY_RUN_GAS_MARGIN_PERCENT / 100;
+        if required > self.gas_budget {
+            return Err(ConnectorError::GasBudgetExceeded {
+                required,
+                budget: self.gas_budget,
+            });
+        }
+        log::debug!("sui: dry run gas cost {required}, gas budget {gas_budget}");
+        Ok(gas_budget.min(self.gas_budget))
+    }
+
     pub fn get_sui_address(&self) -> Result<SuiAddress, ConnectorError> {
-        let key_pair = SuiKeyPair::decode_base64(&self.secret_key)
-            .map_err(|_| ConnectorError::InvalidSecretKey)?;
-        let sui_address: SuiAddress = (&key_pair.public()).into();
-        Ok(sui_address)
+        Ok(self.sender)
     }
 
-    fn get_key_store(&self) -> Result<InMemKeystore, ConnectorError> {
-        let key_pair = SuiKeyPair::decode_base64(&self.secret_key)
-            .map_err(|_| ConnectorError::InvalidSecretKey)?;
+    pub fn get_sponsor_address(&self) -> Result<Option<SuiAddress>, ConnectorError> {
+        Ok(self.sponsor)
+    }
 
-        let mut key_store = InMemKeystore::default();
-        key_store
-            .add_key(None, key_pair)
-            .map_err(|_| ConnectorError::InvalidSecretKey)?;
-        Ok(key_store)
+    /// Address paying for gas, the sponsor when there is one
+    pub fn gas_owner(&self) -> Result<SuiAddress, ConnectorError> {
+        match self.get_sponsor_address()? {
+            Some(sponsor) => Ok(sponsor),
+            None => self.get_sui_address(),
+        }
     }
 
     pub async fn sign_and_execute_tx(
         &self,
         tx_data: TransactionData,
     ) -> Result<SuiTransactionBlockResponse, ConnectorError> {
-        let key_store = self.get_key_store()?;
-        let owner = self.get_sui_address()?;
-        let signature = key_store
-            .sign_secure(&owner, &tx_data, Intent::sui_transaction())
-            .map_err(|err| ConnectorError::SuiTransaction(err.to_string()))?;
+        // Sponsored transactions need the gas owner's signature as well
+        let mut signers = vec![tx_data.sender()];
+        if tx_data.gas_owner() != tx_data.sender() {
+            signers.push(tx_data.gas_owner());
+        }
+        let signatures = signers
+            .iter()
+            .map(|signer| {
+                self.key_store
+                    .sign_secure(signer, &tx_data, Intent::sui_transaction())
+                    .map_err(|err| ConnectorError::SuiTransaction(err.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let tx = Transaction::from_data(tx_data, vec![signature]);
+        let tx = Transaction::from_data(tx_data, signatures);
         let transaction = self
             .client
             .with_sui(|sui| {
                 sui.quorum_driver_api().execute_transaction_block(
-                    Transaction::from(tx.clone()),
+                    tx.clone(),
                     SuiTransactionBlockResponseOptions::full_content(),
-                    Some(sui_types::quorum_driver_types::ExecuteTransactionRequestType::WaitForLocalExecution),
+                    Some(ExecuteTransactionRequestType::WaitForLocalExecution),
                 )
             })
-            .await.unwrap();
+            .await?;
         Ok(transaction)
     }
 }
 
+/// Resolve `key` to a key pair, by alias when a keystore file is loaded, and add it to `key_store`
+fn add_key(
+    key_store: &mut InMemKeystore,
+    keystore: Option<&FileBasedKeystore>,
+    key: &str,
+) -> Result<SuiAddress, ConnectorError> {
+    let key_pair = match keystore {
+        Some(keystore) => {
+            let address = keystore
+                .get_address_by_alias(key.to_string())
+                .map_err(|_| ConnectorError::UnknownAlias(key.to_string()))?;
+            keystore
+                .get_key(address)
+                .map_err(|e| ConnectorError::Keystore(e.to_string()))?
+                .copy()
+        }
+        None if key.starts_with(SUI_PRIV_KEY_PREFIX) => {
+            SuiKeyPair::decode(key).map_err(|_| ConnectorError::InvalidSecretKey)?
+        }
+        None => SuiKeyPair::decode_base64(key).map_err(|_| ConnectorError::InvalidSecretKey)?,
+    };
+    let address: SuiAddress = (&key_pair.public()).into();
+    log::debug!("sui: loaded key of {address}");
+    key_store
+        .add_key(None, key_pair)
+        .map_err(|e| ConnectorError::Keystore(e.to_string()))?;
+    Ok(address)
+}
+
+fn parameter_mutability(
+    parameters: &[bool],
+    call: &MoveCallSpec,
+    position: usize,
+) -> Result<bool, ConnectorError> {
+    parameters.get(position).copied().ok_or_else(|| {
+        ConnectorError::InvalidArgument(format!(
+            "{}::{} has no parameter {position}",
+            call.module, call.function
+        ))
+    })
+}
+
+fn object_id(value: &str) -> Result<ObjectID, ConnectorError> {
+    ObjectID::from_hex_literal(value).map_err(|e| ConnectorError::InvalidArgument(e.to_string()))
+}
+
 impl ClientWrapper {
     pub fn new(primary: SuiClient, backup: Option<SuiClient>) -> Self {
         Self {
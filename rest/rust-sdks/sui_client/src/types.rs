@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::digests::TransactionDigest;
+use sui_types::gas::GasCostSummary;
 
 /// Request for /get_proof rest api
 #[derive(Serialize, Deserialize, Debug)]
@@ -16,3 +19,24 @@ pub struct PullResponseSui {
     pub merkle_root_object: String,
     pub proof_bytes: String,
 }
+
+/// Oracle price of a pair, the canonical `PriceData` returned by the proof verification
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PriceData {
+    pub pair_index: u32,
+    pub value: u128,
+    pub decimal: u16,
+    pub timestamp: u128,
+    pub round: u64,
+}
+
+/// Executed proof transaction
+#[derive(Debug, Clone)]
+pub struct SuiSubmission {
+    pub digest: TransactionDigest,
+    pub checkpoint: Option<u64>,
+    pub gas_used: GasCostSummary,
+    pub events: Vec<SuiEvent>,
+    /// Prices decoded from the oracle events, an error here does not mean the transaction failed
+    pub prices: Result<Vec<PriceData>, String>,
+}
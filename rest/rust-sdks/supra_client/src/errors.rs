@@ -20,8 +20,23 @@ pub enum ConnectorError {
     Signature(#[from] ed25519_dalek::SignatureError),
     #[error(transparent)]
     CryptoMaterial(#[from] CryptoMaterialError),
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+    #[error("invalid move identifier: {0}")]
+    InvalidIdentifier(String),
+    #[error("unable to decode view function result: {0}")]
+    ViewDecode(String),
     #[error("Error with Supra callback  err:{0}")]
     SupraTransaction(String),
+    #[error("Supra transaction {tx_hash} has unknown status {status}")]
+    UnknownTxStatus { tx_hash: String, status: String },
+    #[error("Timed out waiting for supra transaction {0}")]
+    SupraReceiptTimeout(String),
+    #[error("Supra rpc responded with {status}: {message}")]
+    SupraRpc {
+        status: reqwest::StatusCode,
+        message: String,
+    },
     #[error("Error while sending transaction to supra: {0}")]
     SupraExecuteTransactionError(#[from] reqwest::Error),
     #[error(transparent)]
@@ -1,6 +1,8 @@
 pub mod errors;
 pub mod supra_connector;
+pub mod supra_rpc_client;
 pub mod types;
+pub mod view;
 
 use crate::types::PullResponseSupra;
 use reqwest::Client as HttpClient;
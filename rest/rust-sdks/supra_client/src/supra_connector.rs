@@ -1,63 +1,43 @@
 use crate::errors::ConnectorError;
-use crate::types::{PullResponseSupra, SupraAccountResponse, SupraTransaction};
-use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
+use crate::supra_rpc_client::{rejection_status, SupraRpcClient};
+use crate::types::{
+    PairPrice, PullResponseSupra, SupraSimulation, SupraTransaction, SupraTransactionOutput,
+    SupraTxReceipt, SupraTxStatus, SupraViewRequest,
+};
+use crate::view::{decode_pair_price, MoveViewArg, PriceSource};
+use aptos_sdk::crypto::ed25519::{Ed25519PrivateKey, Ed25519Signature};
 use aptos_sdk::crypto::{PrivateKey, SigningKey};
 use aptos_sdk::move_types::account_address::AccountAddress;
 use aptos_sdk::move_types::identifier::Identifier;
 use aptos_sdk::move_types::language_storage::ModuleId;
 use aptos_sdk::transaction_builder::TransactionFactory;
 use aptos_types::chain_id::ChainId;
-use aptos_types::transaction::{EntryFunction, SignedTransaction, TransactionPayload};
+use aptos_types::transaction::{
+    EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
+};
+use aptos_types::vm_status::StatusCode;
 use ed25519_dalek::{PublicKey, SecretKey};
-use reqwest::Url;
+use sequence_manager::SequenceManager;
 use sha3::Digest;
 use std::time::Duration;
 use tiny_keccak::{Hasher, Sha3};
 
 const MODULE: &str = "<CONTRACT MODULE>"; // Module name of your contract. Ex. pull_example
 const ENTRY: &str = "<CONTRACT FUNCTION>"; // Module function name of your contract. Ex. get_pair_price
-const SUPRA_TX_PATH: &str = "/rpc/v1/transactions/submit";
-const SUPRA_ACCOUNTS_PATH: &str = "rpc/v1/accounts/";
-const SUPRA_CHAIN_ID_PATH: &str = "rpc/v1/transactions/chain_id";
-const DEFAULT_TIMEOUT_FOR_REQUEST: Duration = Duration::from_secs(10);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_SUBMIT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SEQUENCE_RESYNCS: usize = 3; // Resubmissions allowed after the node rejects a sequence number
 
 pub async fn invoke_supra_chain(payload: PullResponseSupra, supra_connector: SupraConnector) {
-    let account = Account::from_secret_key(supra_connector.secret_key).unwrap();
-
-    let address = AccountAddress::from_hex_literal(&supra_connector.sc_addr).unwrap();
-    let bytes = hex::decode(&payload.proof_bytes).unwrap();
-
-    let tx_args = TransactionPayload::EntryFunction(EntryFunction::new(
-        ModuleId::new(address, Identifier::new(MODULE).unwrap()),
-        Identifier::new(ENTRY).unwrap(),
-        vec![],
-        vec![bcs::to_bytes(&bytes).unwrap()],
-    ));
-
-    let sequence_number = get_sequence_for_supra(
-        &supra_connector.network_base_path,
-        account.to_address().unwrap().to_string(),
-    )
-        .await
-        .unwrap();
-    let chain_id: u8 = get_chain_id_for_supra(&supra_connector.network_base_path)
-        .await
-        .unwrap();
-
-    let tx = account
-        .setup_supra_transaction(
-            &tx_args,
-            sequence_number,
-            chain_id,
-            supra_connector.gas_budget,
-        )
-        .await
-        .unwrap();
-
-    let tx = SupraTransaction::Move(tx);
-    match send_supra_tx(supra_connector.network_base_path, tx).await {
-        Ok(tx_hash) => {
-            log::info!("sent tx, data : {}", tx_hash);
+    match supra_connector.submit_proof(&payload).await {
+        Ok(receipt) => {
+            log::info!(
+                "tx {} finished with status {:?}",
+                receipt.hash,
+                receipt.status
+            );
         }
         Err(e) => log::error!("{e}"),
     }
@@ -68,6 +48,10 @@ pub struct SupraConfig<'a> {
     client_url: &'a str,
     sc_address: &'a str,
     gas_budget: u64,
+    request_timeout: Duration,
+    submit_timeout: Duration,
+    receipt_timeout: Duration,
+    receipt_poll_interval: Duration,
 }
 
 impl<'a> SupraConfig<'a> {
@@ -82,8 +66,36 @@ impl<'a> SupraConfig<'a> {
             client_url,
             sc_address,
             gas_budget,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            submit_timeout: DEFAULT_SUBMIT_TIMEOUT,
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            receipt_poll_interval: DEFAULT_RECEIPT_POLL_INTERVAL,
         }
     }
+
+    /// Set the timeout of rpc requests
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Set the timeout of transaction submissions
+    pub fn with_submit_timeout(mut self, submit_timeout: Duration) -> Self {
+        self.submit_timeout = submit_timeout;
+        self
+    }
+
+    /// Set how long to wait for a submitted transaction to leave the pending state
+    pub fn with_receipt_timeout(mut self, receipt_timeout: Duration) -> Self {
+        self.receipt_timeout = receipt_timeout;
+        self
+    }
+
+    /// Set the delay between two transaction status requests
+    pub fn with_receipt_poll_interval(mut self, receipt_poll_interval: Duration) -> Self {
+        self.receipt_poll_interval = receipt_poll_interval;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -91,7 +103,10 @@ pub struct SupraConnector {
     secret_key: String,
     sc_addr: String,
     gas_budget: u64,
-    network_base_path: Url,
+    rpc: SupraRpcClient,
+    sequence_manager: SequenceManager,
+    receipt_timeout: Duration,
+    receipt_poll_interval: Duration,
 }
 
 impl SupraConnector {
@@ -102,14 +117,202 @@ impl SupraConnector {
             .map_err(|_| ConnectorError::InvalidUrl)?;
 
         Ok(Self {
-            network_base_path: url,
+            rpc: SupraRpcClient::new(url, conf.request_timeout, conf.submit_timeout)?,
             secret_key: conf.secret_key.to_string(),
             sc_addr: conf.sc_address.to_string(),
             gas_budget: conf.gas_budget,
+            sequence_manager: SequenceManager::new(),
+            receipt_timeout: conf.receipt_timeout,
+            receipt_poll_interval: conf.receipt_poll_interval,
+        })
+    }
+
+    /// Submit the proof verification transaction and wait for its receipt.
+    /// Sequence numbers are handed out locally, clones of the connector can submit concurrently.
+    /// The local counter is resynced from chain when the node rejects a sequence number or when the
+    /// submission outcome is unknown.
+    pub async fn submit_proof(
+        &self,
+        payload: &PullResponseSupra,
+    ) -> Result<SupraTxReceipt, ConnectorError> {
+        let account = Account::from_secret_key(self.secret_key.clone())?;
+        let address = account.to_address()?.to_string();
+        let tx_args = self.proof_payload(payload)?;
+        let chain_id = self.rpc.chain_id().await?;
+
+        let mut resyncs = 0;
+        let tx_hash = loop {
+            let sequence_number = self
+                .sequence_manager
+                .reserve(|| self.rpc.sequence_number(&address))
+                .await?;
+            let tx = match account
+                .setup_supra_transaction(&tx_args, sequence_number, chain_id, self.gas_budget)
+                .await
+            {
+                Ok(tx) => tx,
+                Err(e) => {
+                    // Nothing was sent, the number can be handed out again
+                    self.sequence_manager.release(sequence_number).await;
+                    return Err(e);
+                }
+            };
+            match self.rpc.submit(&SupraTransaction::Move(tx)).await {
+                Ok(tx_hash) => break tx_hash,
+                Err(e) if is_sequence_number_rejection(&e) && resyncs < MAX_SEQUENCE_RESYNCS => {
+                    log::warn!("supra: sequence number {sequence_number} rejected, resyncing: {e}");
+                    self.sequence_manager.reset().await;
+                    resyncs += 1;
+                }
+                Err(e @ ConnectorError::SupraRpc { .. }) => {
+                    // The node answered with an error, the transaction is not in the mempool
+                    self.sequence_manager.release(sequence_number).await;
+                    return Err(e);
+                }
+                Err(e) => {
+                    // The transaction may have been accepted, the number can not be reused
+                    self.sequence_manager.reset().await;
+                    return Err(e);
+                }
+            }
+        };
+        let receipt = self.wait_for_receipt(&tx_hash).await;
+        if matches!(
+            receipt,
+            Err(_)
+                | Ok(SupraTxReceipt {
+                    status: SupraTxStatus::Expired | SupraTxStatus::Invalid,
+                    ..
+                })
+        ) {
+            // An uncommitted transaction leaves a gap later sequence numbers would wait on
+            log::warn!("supra: {tx_hash} not committed, resyncing sequence number");
+            self.sequence_manager.reset().await;
+        }
+        receipt
+    }
+
+    /// Rpc client of the connector
+    pub fn rpc(&self) -> &SupraRpcClient {
+        &self.rpc
+    }
+
+    /// Poll the transaction until it is committed, failed or expired
+    pub async fn wait_for_receipt(&self, tx_hash: &str) -> Result<SupraTxReceipt, ConnectorError> {
+        let deadline = tokio::time::Instant::now() + self.receipt_timeout;
+        loop {
+            if let Some(response) = self.rpc.transaction(tx_hash).await? {
+                if let SupraTxStatus::Unknown(status) = response.status {
+                    return Err(ConnectorError::UnknownTxStatus {
+                        tx_hash: tx_hash.to_string(),
+                        status,
+                    });
+                }
+                if response.status.is_terminal() {
+                    return Ok(SupraTxReceipt {
+                        hash: tx_hash.to_string(),
+                        status: response.status,
+                        output: response
+                            .output
+                            .map(|SupraTransactionOutput::Move(output)| output),
+                    });
+                }
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ConnectorError::SupraReceiptTimeout(tx_hash.to_string()));
+            }
+            tokio::time::sleep(self.receipt_poll_interval).await;
+        }
+    }
+
+    /// Call a move view function, `function` being fully qualified as `address::module::function`
+    pub async fn view(
+        &self,
+        function: &str,
+        type_arguments: &[&str],
+        arguments: &[MoveViewArg],
+    ) -> Result<Vec<serde_json::Value>, ConnectorError> {
+        let request = SupraViewRequest {
+            function: function.to_string(),
+            type_arguments: type_arguments.iter().map(|t| t.to_string()).collect(),
+            arguments: arguments.iter().map(MoveViewArg::to_json).collect(),
+        };
+        self.rpc.view(&request).await
+    }
+
+    /// Read the latest on-chain price of a pair, e.g. to confirm a submitted proof landed
+    pub async fn get_pair_price(
+        &self,
+        source: &PriceSource,
+        pair_index: u32,
+    ) -> Result<PairPrice, ConnectorError> {
+        let result = self
+            .view(&source.function(), &[], &[MoveViewArg::U32(pair_index)])
+            .await?;
+        decode_pair_price(pair_index, &result)
+    }
+
+    /// Entry function payload verifying the given proof
+    pub fn proof_payload(
+        &self,
+        payload: &PullResponseSupra,
+    ) -> Result<TransactionPayload, ConnectorError> {
+        let address = AccountAddress::from_hex_literal(&self.sc_addr)?;
+        let bytes = hex::decode(&payload.proof_bytes)?;
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(address, identifier(MODULE)?),
+            identifier(ENTRY)?,
+            vec![],
+            vec![bcs::to_bytes(&bytes)?],
+        )))
+    }
+
+    /// Simulate the proof verification without committing it on chain
+    pub async fn simulate_proof(
+        &self,
+        payload: &PullResponseSupra,
+    ) -> Result<SupraSimulation, ConnectorError> {
+        let account = Account::from_secret_key(self.secret_key.clone())?;
+        let tx_args = self.proof_payload(payload)?;
+        let sequence_number = self
+            .rpc
+            .sequence_number(&account.to_address()?.to_string())
+            .await?;
+        let chain_id = self.rpc.chain_id().await?;
+
+        let tx =
+            account.setup_supra_simulation(&tx_args, sequence_number, chain_id, self.gas_budget)?;
+        let response = self.rpc.simulate(&SupraTransaction::Move(tx)).await?;
+        let Some(SupraTransactionOutput::Move(output)) = response.output else {
+            return Err(ConnectorError::SupraTransaction(format!(
+                "simulation returned no output, status: {:?}",
+                response.status
+            )));
+        };
+        Ok(SupraSimulation {
+            success: response.status == SupraTxStatus::Success,
+            vm_status: output.vm_status,
+            gas_used: output.gas_used,
+            events: output.events,
         })
     }
 }
 
+/// Whether the node rejected a transaction because of its sequence number
+fn is_sequence_number_rejection(err: &ConnectorError) -> bool {
+    let ConnectorError::SupraRpc { message, .. } = err else {
+        return false;
+    };
+    matches!(
+        rejection_status(message),
+        Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD | StatusCode::SEQUENCE_NUMBER_TOO_NEW)
+    )
+}
+
+fn identifier(name: &str) -> Result<Identifier, ConnectorError> {
+    Identifier::new(name).map_err(|_| ConnectorError::InvalidIdentifier(name.to_string()))
+}
+
 #[derive(Clone)]
 pub struct Account {
     auth_key: String,
@@ -152,14 +355,7 @@ impl Account {
         chain_id: u8,
         gas_limit: u64,
     ) -> Result<SignedTransaction, ConnectorError> {
-        let transaction_factory = TransactionFactory::new(ChainId::new(chain_id))
-            .with_max_gas_amount(gas_limit)
-            .with_transaction_expiration_time(300);
-        let raw_tx = transaction_factory
-            .payload(payload.clone())
-            .sender(self.to_address()?)
-            .sequence_number(sequence_number)
-            .build();
+        let raw_tx = self.build_raw_transaction(payload, sequence_number, chain_id, gas_limit)?;
         let supra_hash_prefix = b"SUPRA::RawTransaction";
         let mut finalised_bytes = Self::create_sha3_hash(supra_hash_prefix);
         // Unwrap safety as RawTransaction already support Serialise trait
@@ -172,45 +368,44 @@ impl Account {
         Ok(signed_tx)
     }
 
+    /// Constructs a transaction from a payload with a zeroed signature, as expected by the
+    /// simulate endpoint
+    pub fn setup_supra_simulation(
+        &self,
+        payload: &TransactionPayload,
+        sequence_number: u64,
+        chain_id: u8,
+        gas_limit: u64,
+    ) -> Result<SignedTransaction, ConnectorError> {
+        let raw_tx = self.build_raw_transaction(payload, sequence_number, chain_id, gas_limit)?;
+        let signature = Ed25519Signature::try_from(&[0u8; 64][..])?;
+        Ok(SignedTransaction::new(
+            raw_tx,
+            self.sender_key.public_key(),
+            signature,
+        ))
+    }
+
+    fn build_raw_transaction(
+        &self,
+        payload: &TransactionPayload,
+        sequence_number: u64,
+        chain_id: u8,
+        gas_limit: u64,
+    ) -> Result<RawTransaction, ConnectorError> {
+        let transaction_factory = TransactionFactory::new(ChainId::new(chain_id))
+            .with_max_gas_amount(gas_limit)
+            .with_transaction_expiration_time(300);
+        Ok(transaction_factory
+            .payload(payload.clone())
+            .sender(self.to_address()?)
+            .sequence_number(sequence_number)
+            .build())
+    }
+
     pub fn create_sha3_hash(bytes: &[u8]) -> Vec<u8> {
         let mut hasher = sha3::Sha3_256::new();
         hasher.update(bytes);
         hasher.finalize().to_vec()
     }
 }
-
-async fn send_supra_tx(url: Url, tx: SupraTransaction) -> Result<String, ConnectorError> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    let tx_url = url.join(SUPRA_TX_PATH)?;
-    let res = client.post(tx_url).json(&tx).send().await?;
-    let status = res.status();
-    let response = res.text().await?;
-    if status != 200 {
-        return Err(ConnectorError::SupraTransaction(response));
-    }
-    Ok(response)
-}
-pub async fn get_sequence_for_supra(
-    client_url: &Url,
-    address: String,
-) -> Result<u64, ConnectorError> {
-    let client = reqwest::Client::builder()
-        .timeout(DEFAULT_TIMEOUT_FOR_REQUEST)
-        .build()?;
-    let acc_url = client_url.join(&format!("{}{}", SUPRA_ACCOUNTS_PATH, address))?;
-    let acc_json: SupraAccountResponse = client.get(acc_url).send().await?.json().await?;
-
-    Ok(acc_json.sequence_number)
-}
-
-async fn get_chain_id_for_supra(client_url: &Url) -> Result<u8, ConnectorError> {
-    let client = reqwest::Client::builder()
-        .timeout(DEFAULT_TIMEOUT_FOR_REQUEST)
-        .build()?;
-    let chain_id_url = client_url.join(SUPRA_CHAIN_ID_PATH)?;
-    let chain_id_bytes = client.get(chain_id_url).send().await?.text().await?;
-    let chain_id: u8 = chain_id_bytes.parse()?;
-    Ok(chain_id)
-}
@@ -0,0 +1,167 @@
+use crate::errors::ConnectorError;
+use crate::types::{
+    SupraAccountResponse, SupraTransaction, SupraTransactionResponse, SupraViewRequest,
+    SupraViewResponse,
+};
+use aptos_types::vm_status::StatusCode as VmStatusCode;
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+const SUPRA_TX_PATH: &str = "rpc/v1/transactions/submit";
+const SUPRA_SIMULATE_PATH: &str = "rpc/v1/transactions/simulate";
+const SUPRA_TRANSACTIONS_PATH: &str = "rpc/v1/transactions/";
+const SUPRA_ACCOUNTS_PATH: &str = "rpc/v1/accounts/";
+const SUPRA_CHAIN_ID_PATH: &str = "rpc/v1/transactions/chain_id";
+const SUPRA_VIEW_PATH: &str = "rpc/v1/view";
+/// VM statuses the node rejects a transaction with before it reaches the mempool
+const REJECTION_STATUSES: [VmStatusCode; 2] = [
+    VmStatusCode::SEQUENCE_NUMBER_TOO_OLD,
+    VmStatusCode::SEQUENCE_NUMBER_TOO_NEW,
+];
+
+/// Typed client for the supra rpc api.
+/// It shares one pooled http client across requests and caches the chain id after first use,
+/// clones share both.
+#[derive(Clone)]
+pub struct SupraRpcClient {
+    client: reqwest::Client,
+    base_url: Url,
+    submit_timeout: Duration,
+    chain_id: Arc<OnceCell<u8>>,
+}
+
+impl SupraRpcClient {
+    pub fn new(
+        base_url: Url,
+        request_timeout: Duration,
+        submit_timeout: Duration,
+    ) -> Result<Self, ConnectorError> {
+        let client = reqwest::Client::builder()
+            .timeout(request_timeout)
+            .build()?;
+        Ok(Self {
+            client,
+            base_url,
+            submit_timeout,
+            chain_id: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Chain id of the network, only fetched on first use
+    pub async fn chain_id(&self) -> Result<u8, ConnectorError> {
+        self.chain_id
+            .get_or_try_init(|| async {
+                let url = self.base_url.join(SUPRA_CHAIN_ID_PATH)?;
+                let response = Self::send(self.client.get(url)).await?;
+                Ok::<_, ConnectorError>(response.text().await?.trim().parse::<u8>()?)
+            })
+            .await
+            .copied()
+    }
+
+    /// Current on-chain sequence number of `address`
+    pub async fn sequence_number(&self, address: &str) -> Result<u64, ConnectorError> {
+        let url = self
+            .base_url
+            .join(&format!("{}{}", SUPRA_ACCOUNTS_PATH, address))?;
+        let account: SupraAccountResponse = Self::json(self.client.get(url)).await?;
+        Ok(account.sequence_number)
+    }
+
+    /// Submit a signed transaction, returning its hash
+    pub async fn submit(&self, tx: &SupraTransaction) -> Result<String, ConnectorError> {
+        let url = self.base_url.join(SUPRA_TX_PATH)?;
+        let request = self.client.post(url).timeout(self.submit_timeout).json(tx);
+        let response = Self::send(request).await?.text().await?;
+        parse_tx_hash(&response)
+    }
+
+    /// Execute a transaction signed with a zeroed signature without committing it
+    pub async fn simulate(
+        &self,
+        tx: &SupraTransaction,
+    ) -> Result<SupraTransactionResponse, ConnectorError> {
+        let url = self.base_url.join(SUPRA_SIMULATE_PATH)?;
+        Self::json(self.client.post(url).json(tx)).await
+    }
+
+    /// Look a transaction up by hash, `None` while the node does not know it yet
+    pub async fn transaction(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<SupraTransactionResponse>, ConnectorError> {
+        let url = self
+            .base_url
+            .join(&format!("{}{}", SUPRA_TRANSACTIONS_PATH, tx_hash))?;
+        match Self::json(self.client.get(url)).await {
+            Err(ConnectorError::SupraRpc { status, .. }) if status == StatusCode::NOT_FOUND => {
+                Ok(None)
+            }
+            res => res,
+        }
+    }
+
+    /// Call a move view function
+    pub async fn view(
+        &self,
+        request: &SupraViewRequest,
+    ) -> Result<Vec<serde_json::Value>, ConnectorError> {
+        let url = self.base_url.join(SUPRA_VIEW_PATH)?;
+        let response: SupraViewResponse = Self::json(self.client.post(url).json(request)).await?;
+        Ok(response.result)
+    }
+
+    async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, ConnectorError> {
+        Ok(Self::send(request).await?.json().await?)
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Response, ConnectorError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await?;
+            return Err(ConnectorError::SupraRpc { status, message });
+        }
+        Ok(response)
+    }
+}
+
+/// The submit endpoint answers with the transaction hash as a json string
+fn parse_tx_hash(response: &str) -> Result<String, ConnectorError> {
+    let tx_hash = serde_json::from_str::<String>(response)
+        .unwrap_or_else(|_| response.trim().trim_matches('"').to_string());
+    if tx_hash.is_empty() {
+        return Err(ConnectorError::SupraTransaction(format!(
+            "no transaction hash in submit response: {response}"
+        )));
+    }
+    Ok(tx_hash)
+}
+
+/// Json error body of the rpc api
+#[derive(Deserialize)]
+struct RpcErrorBody {
+    vm_error_code: Option<u64>,
+}
+
+/// VM status a transaction was rejected with, given the body of the error response. Read from the
+/// `vm_error_code` of a json body, or from the status name of a plain text one.
+pub fn rejection_status(message: &str) -> Option<VmStatusCode> {
+    if let Ok(RpcErrorBody {
+        vm_error_code: Some(code),
+    }) = serde_json::from_str(message)
+    {
+        return VmStatusCode::try_from(code).ok();
+    }
+    message
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .find_map(|token| {
+            REJECTION_STATUSES
+                .into_iter()
+                .find(|status| format!("{status:?}") == token)
+        })
+}
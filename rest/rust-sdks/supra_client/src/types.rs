@@ -27,4 +27,101 @@ pub enum SupraTransaction {
 pub struct SupraAccountResponse {
     pub sequence_number: u64,
     pub authentication_key: String,
-}
\ No newline at end of file
+}
+
+/// Response format for `/transactions/{hash}` and `/transactions/simulate` rest api from supra
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupraTransactionResponse {
+    pub status: SupraTxStatus,
+    pub output: Option<SupraTransactionOutput>,
+}
+
+/// Execution status of a supra transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SupraTxStatus {
+    Success,
+    #[serde(alias = "Fail")]
+    Failed,
+    /// Rejected before execution
+    Invalid,
+    /// Not executed before its expiration time
+    Expired,
+    Pending,
+    /// Status this client does not know about, it is not assumed to be pending
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl SupraTxStatus {
+    /// Whether the transaction reached a final status
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Success | Self::Failed | Self::Invalid | Self::Expired
+        )
+    }
+}
+
+/// Execution output of a supra transaction
+#[derive(Serialize, Deserialize, Debug)]
+pub enum SupraTransactionOutput {
+    Move(SupraMoveOutput),
+}
+
+/// Execution output of a supra move transaction
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SupraMoveOutput {
+    pub gas_used: u64,
+    pub vm_status: String,
+    pub events: Vec<SupraEvent>,
+}
+
+/// Event emitted by a supra move transaction
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SupraEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: serde_json::Value,
+}
+
+/// Outcome of a transaction simulated against a supra node
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupraSimulation {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub events: Vec<SupraEvent>,
+}
+
+/// Receipt of a supra transaction once it is no longer pending
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupraTxReceipt {
+    pub hash: String,
+    pub status: SupraTxStatus,
+    /// Execution output, `None` when the node has none, e.g. for transactions that never executed
+    pub output: Option<SupraMoveOutput>,
+}
+
+/// Request format for `/view` rest api from supra
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupraViewRequest {
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// Response format for `/view` rest api from supra
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupraViewResponse {
+    pub result: Vec<serde_json::Value>,
+}
+
+/// Latest price of a pair as stored on chain
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PairPrice {
+    pub pair_index: u32,
+    pub price: u128,
+    pub decimal: u16,
+    pub timestamp: u64,
+    pub round: u64,
+}
@@ -0,0 +1,100 @@
+use crate::errors::ConnectorError;
+use crate::types::PairPrice;
+use aptos_sdk::move_types::account_address::AccountAddress;
+use serde_json::Value;
+
+const ORACLE_STORAGE_MODULE: &str = "supra_oracle_storage"; // Module of the oracle holder storing the prices
+const ORACLE_GET_PRICE: &str = "get_price"; // View function returning (price, decimal, timestamp, round)
+
+/// Argument of a move view function, encoded the way the supra rpc expects it in json
+#[derive(Clone, Debug)]
+pub enum MoveViewArg {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Address(AccountAddress),
+    Bytes(Vec<u8>),
+    String(String),
+    Vector(Vec<MoveViewArg>),
+}
+
+impl MoveViewArg {
+    /// Small integers are json numbers, 64 and 128 bit integers are strings to keep precision
+    pub fn to_json(&self) -> Value {
+        match self {
+            MoveViewArg::Bool(v) => Value::from(*v),
+            MoveViewArg::U8(v) => Value::from(*v),
+            MoveViewArg::U16(v) => Value::from(*v),
+            MoveViewArg::U32(v) => Value::from(*v),
+            MoveViewArg::U64(v) => Value::from(v.to_string()),
+            MoveViewArg::U128(v) => Value::from(v.to_string()),
+            MoveViewArg::Address(v) => Value::from(v.to_hex_literal()),
+            MoveViewArg::Bytes(v) => Value::from(format!("0x{}", hex::encode(v))),
+            MoveViewArg::String(v) => Value::from(v.as_str()),
+            MoveViewArg::Vector(v) => Value::Array(v.iter().map(MoveViewArg::to_json).collect()),
+        }
+    }
+}
+
+/// Module a pair price is read from
+#[derive(Clone, Debug)]
+pub enum PriceSource {
+    /// Supra oracle holder, read through `supra_oracle_storage::get_price`
+    OracleHolder { address: AccountAddress },
+    /// Consumer contract exposing a view function taking the pair index and returning
+    /// `(price, decimal, timestamp, round)`, like the oracle holder
+    Consumer {
+        address: AccountAddress,
+        module: String,
+        function: String,
+    },
+}
+
+impl PriceSource {
+    /// Fully qualified view function returning the price of a pair
+    pub fn function(&self) -> String {
+        match self {
+            PriceSource::OracleHolder { address } => format!(
+                "{}::{}::{}",
+                address.to_hex_literal(),
+                ORACLE_STORAGE_MODULE,
+                ORACLE_GET_PRICE
+            ),
+            PriceSource::Consumer {
+                address,
+                module,
+                function,
+            } => format!("{}::{}::{}", address.to_hex_literal(), module, function),
+        }
+    }
+}
+
+/// Decode the `(price, decimal, timestamp, round)` tuple returned by a price view function
+pub fn decode_pair_price(pair_index: u32, result: &[Value]) -> Result<PairPrice, ConnectorError> {
+    let [price, decimal, timestamp, round] = result else {
+        return Err(ConnectorError::ViewDecode(format!(
+            "expected (price, decimal, timestamp, round), got {result:?}"
+        )));
+    };
+    Ok(PairPrice {
+        pair_index,
+        price: decode_integer(price)?,
+        decimal: decode_integer(decimal)?,
+        timestamp: decode_integer(timestamp)?,
+        round: decode_integer(round)?,
+    })
+}
+
+fn decode_integer<T: TryFrom<u128>>(value: &Value) -> Result<T, ConnectorError> {
+    let integer = match value {
+        Value::String(v) => v.parse::<u128>().ok(),
+        Value::Number(v) => v.as_u64().map(u128::from),
+        _ => None,
+    };
+    integer
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or_else(|| ConnectorError::ViewDecode(format!("invalid move integer: {value}")))
+}
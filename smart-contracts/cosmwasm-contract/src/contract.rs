@@ -1,21 +1,26 @@
 use crate::error::{ContractError, ParseReplyError};
-use crate::msg::{ContractResponse, ExecuteMsg, InstantiateMsg, OracleHolder, PriceData, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{
+    ContractResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, OracleHolder, OwnerResponse,
+    PairsResponse, PriceData, QueryMsg, SupraPullExecuteMsg,
+};
+use crate::state::{State, LEGACY_STATE, PENDING_OWNER, PRICE_DATA, STATE};
 use anybuf::Bufany;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
-    SubMsg,
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, SubMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use serde_json::json;
-use std::collections::HashMap;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:osmo";
-const CONTRACT_VERSION: &str = "0.1.1";
+const CONTRACT_VERSION: &str = "0.2.0";
 const VERIFY_PROOF_REPLY_ID: u64 = 1;
+const DEFAULT_LIST_LIMIT: u32 = 10;
+const MAX_LIST_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -27,7 +32,6 @@ pub fn instantiate(
     let state = State {
         supra_pull_contract: msg.supra_pull_contract.clone(),
         owner: info.sender.clone(),
-        price_data: HashMap::new(),
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
@@ -42,19 +46,60 @@ pub fn instantiate(
 pub fn execute(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateSupraContract {
             supra_pull_contract,
-        } => update_supra_contract(deps, supra_pull_contract),
+        } => update_supra_contract(deps, info, supra_pull_contract),
         ExecuteMsg::VerifyOracleProof { proof_bytes } => {
             client_verify_oracle_proof(deps, proof_bytes)
         }
+        ExecuteMsg::ProposeOwner { new_owner } => propose_owner(deps, info, new_owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
     }
 }
 
+fn ensure_owner(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigration {
+            contract: version.contract,
+            version: version.version,
+        });
+    }
+
+    // Move the price data out of `STATE` into its own map, one entry per pair
+    let legacy_state = LEGACY_STATE.load(deps.storage)?;
+    for (pair, oracle_holder) in &legacy_state.price_data {
+        PRICE_DATA.save(deps.storage, *pair, oracle_holder)?;
+    }
+    STATE.save(
+        deps.storage,
+        &State {
+            supra_pull_contract: legacy_state.supra_pull_contract,
+            owner: legacy_state.owner,
+        },
+    )?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", version.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migrated_pairs", legacy_state.price_data.len().to_string()))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     match msg.id {
@@ -75,29 +120,18 @@ fn handle_verify_proof_reply(deps: DepsMut, msg: Reply) -> StdResult<Response> {
     //We have used the first index value from the Buf
     let price_data: Vec<PriceData> =
         bcs::from_bytes(&deserialized_price_data.bytes(1).unwrap()).unwrap();
-    STATE
-        .update(deps.storage, |mut state| -> Result<_, ContractError> {
-            for data in price_data.clone() {
-                if let Some(oracle_holder) = state.price_data.get_mut(&data.pair) {
-                    oracle_holder.price = data.price;
-                    oracle_holder.round = data.round;
-                    oracle_holder.timestamp = data.timestamp;
-                    oracle_holder.decimal = data.decimal;
-                } else {
-                    state.price_data.insert(
-                        data.pair,
-                        OracleHolder {
-                            price: data.price,
-                            timestamp: data.timestamp,
-                            decimal: data.decimal,
-                            round: data.round,
-                        },
-                    );
-                }
-            }
-            Ok(state)
-        })
-        .expect("State Update Panic");
+    for data in &price_data {
+        PRICE_DATA.save(
+            deps.storage,
+            data.pair,
+            &OracleHolder {
+                price: data.price,
+                timestamp: data.timestamp,
+                decimal: data.decimal,
+                round: data.round,
+            },
+        )?;
+    }
 
     let price_data_string = serde_json::to_string(&price_data).unwrap();
     Ok(Response::default().add_attribute("client_data", price_data_string))
@@ -105,8 +139,10 @@ fn handle_verify_proof_reply(deps: DepsMut, msg: Reply) -> StdResult<Response> {
 
 fn update_supra_contract(
     deps: DepsMut,
+    info: MessageInfo,
     supra_pull_contract: String,
 ) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
     STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
         state.supra_pull_contract = supra_pull_contract;
         Ok(state)
@@ -115,20 +151,48 @@ fn update_supra_contract(
     Ok(Response::new().add_attribute("method", "update_supra_contract"))
 }
 
+fn propose_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    PENDING_OWNER.save(deps.storage, &new_owner)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_owner")
+        .add_attribute("pending_owner", new_owner))
+}
+
+fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    if pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.owner = info.sender.clone();
+        Ok(state)
+    })?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_ownership")
+        .add_attribute("owner", info.sender))
+}
+
 fn client_verify_oracle_proof(
     deps: DepsMut,
     proof_bytes: Vec<u8>,
 ) -> Result<Response, ContractError> {
-    let execute_msg = json!({
-        "verify_oracle_proof": {
-            "bytes_proof" : proof_bytes
-        }
-    });
+    let execute_msg = SupraPullExecuteMsg::VerifyOracleProof {
+        bytes_proof: proof_bytes,
+    };
     let verify_oracle_proof = cosmwasm_std::WasmMsg::Execute {
         contract_addr: query_supra_pull_contract(deps.as_ref())
             .unwrap()
             .supra_pull_contract,
-        msg: Binary::from(serde_json::to_vec(&execute_msg).unwrap()),
+        msg: to_json_binary(&execute_msg)?,
         funds: vec![],
     };
 
@@ -149,9 +213,23 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetPairDataSupra { pair_id } => {
             to_json_binary(&query_pair_data_supra(deps, pair_id)?)
         }
+        QueryMsg::GetPairsData { pair_ids } => to_json_binary(&query_pairs_data(deps, pair_ids)?),
+        QueryMsg::ListPairs { start_after, limit } => {
+            to_json_binary(&query_list_pairs(deps, start_after, limit)?)
+        }
+        QueryMsg::GetOwner {} => to_json_binary(&query_owner(deps)?),
     }
 }
 
+fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    let state = STATE.load(deps.storage)?;
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(OwnerResponse {
+        owner: state.owner.to_string(),
+        pending_owner: pending_owner.map(|owner| owner.to_string()),
+    })
+}
+
 fn query_supra_pull_contract(deps: Deps) -> StdResult<ContractResponse> {
     let state = STATE.load(deps.storage)?;
     Ok(ContractResponse {
@@ -173,10 +251,50 @@ fn query_pair_data_supra(deps: Deps, pair_id: u32) -> StdResult<OracleHolder> {
 }
 
 fn query_pair_data_internal(deps: Deps, pair_id: u32) -> StdResult<OracleHolder> {
-    let state = STATE.load(deps.storage)?;
-    if let Some(oracle_holder) = state.price_data.get(&pair_id) {
-        Ok(oracle_holder.clone())
-    } else {
-        panic!("Pair id not found");
+    PRICE_DATA
+        .may_load(deps.storage, pair_id)?
+        .ok_or_else(|| StdError::not_found(format!("OracleHolder for pair {}", pair_id)))
+}
+
+fn query_pairs_data(deps: Deps, pair_ids: Vec<u32>) -> StdResult<PairsResponse> {
+    if pair_ids.len() > MAX_LIST_LIMIT as usize {
+        return Err(StdError::generic_err(format!(
+            "Too many pair ids: {}, at most {} can be queried at once",
+            pair_ids.len(),
+            MAX_LIST_LIMIT
+        )));
+    }
+    let pairs = pair_ids
+        .into_iter()
+        .map(|pair_id| {
+            query_pair_data_internal(deps, pair_id)
+                .map(|oracle_holder| price_data(pair_id, oracle_holder))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PairsResponse { pairs })
+}
+
+fn query_list_pairs(
+    deps: Deps,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).min(MAX_LIST_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let pairs = PRICE_DATA
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(pair_id, oracle_holder)| price_data(pair_id, oracle_holder)))
+        .collect::<StdResult<_>>()?;
+    Ok(PairsResponse { pairs })
+}
+
+fn price_data(pair: u32, oracle_holder: OracleHolder) -> PriceData {
+    PriceData {
+        pair,
+        price: oracle_holder.price,
+        timestamp: oracle_holder.timestamp,
+        decimal: oracle_holder.decimal,
+        round: oracle_holder.round,
     }
 }
@@ -1,11 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use crate::contract::{execute, instantiate, query};
+    use crate::contract::{execute, instantiate, migrate, query};
     use crate::helpers::CwTemplateContract;
-    use crate::msg::{ContractResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::msg::{
+        ContractResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, OracleHolder, OwnerResponse,
+        PairsResponse, QueryMsg,
+    };
+    use crate::state::{LegacyState, LEGACY_STATE, PRICE_DATA};
+    use crate::ContractError;
     use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
     use cosmwasm_std::{coins, from_json};
-    use cosmwasm_std::{Addr, Coin, Empty, Uint128};
+    use cosmwasm_std::{Addr, Coin, Empty, StdError, Uint128};
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 
     pub fn contract_template() -> Box<dyn Contract<Empty>> {
@@ -68,7 +73,23 @@ mod tests {
                 supra_pull_contract: "Test Update".to_string(),
             };
             let cosmos_msg = cw_template_contract.call(msg).unwrap();
-            app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+        }
+
+        #[test]
+        fn test_update_contract_unauthorized_integrated() {
+            let (mut app, cw_template_contract) = test_proper_instantiate();
+
+            let msg = ExecuteMsg::UpdateSupraContract {
+                supra_pull_contract: "Test Update".to_string(),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            let err = app
+                .execute(Addr::unchecked(USER), cosmos_msg)
+                .unwrap_err()
+                .downcast::<ContractError>()
+                .unwrap();
+            assert!(matches!(err, ContractError::Unauthorized {}));
         }
     }
     #[test]
@@ -100,11 +121,18 @@ mod tests {
         let info = mock_info("creator", &coins(2, "token"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // beneficiary can release it
+        // only the owner can update the supra contract
         let info = mock_info("anyone", &coins(2, "token"));
         let msg = ExecuteMsg::UpdateSupraContract {
             supra_pull_contract: "Test Update".to_string(),
         };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateSupraContract {
+            supra_pull_contract: "Test Update".to_string(),
+        };
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Receive the response and unwrap() to fetch the supra oracle pull contract
@@ -112,4 +140,194 @@ mod tests {
         let value: ContractResponse = from_json(&res).unwrap();
         assert_eq!("Test Update".to_string(), value.supra_pull_contract);
     }
+
+    #[test]
+    fn test_ownership_transfer() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            supra_pull_contract: "Test".to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only the owner can propose a new owner
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "successor".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // only the proposed owner can accept
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_json(&res).unwrap();
+        assert_eq!("creator".to_string(), value.owner);
+        assert_eq!(Some("successor".to_string()), value.pending_owner);
+
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("successor", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_json(&res).unwrap();
+        assert_eq!("successor".to_string(), value.owner);
+        assert_eq!(None, value.pending_owner);
+
+        // the previous owner lost its admin rights
+        let msg = ExecuteMsg::UpdateSupraContract {
+            supra_pull_contract: "Test Update".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_migrate_price_data() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let oracle_holder = OracleHolder {
+            price: 1000,
+            timestamp: 1,
+            decimal: 8,
+            round: 2,
+        };
+        let legacy_state = LegacyState {
+            supra_pull_contract: "Test".to_string(),
+            owner: Addr::unchecked("creator"),
+            price_data: [(21, oracle_holder.clone())].into_iter().collect(),
+        };
+        LEGACY_STATE
+            .save(deps.as_mut().storage, &legacy_state)
+            .unwrap();
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:osmo", "0.1.1").unwrap();
+
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairDataInternal { pair_id: 21 },
+        )
+        .unwrap();
+        let value: OracleHolder = from_json(&res).unwrap();
+        assert_eq!(oracle_holder, value);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetSupraPullContract {}).unwrap();
+        let value: ContractResponse = from_json(&res).unwrap();
+        assert_eq!("Test".to_string(), value.supra_pull_contract);
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!("0.2.0", version.version);
+    }
+
+    #[test]
+    fn test_query_pairs() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            supra_pull_contract: "Test".to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for pair_id in [0, 21, 49, 61] {
+            let oracle_holder = OracleHolder {
+                price: pair_id as u128 * 100,
+                timestamp: 1,
+                decimal: 8,
+                round: 2,
+            };
+            PRICE_DATA
+                .save(deps.as_mut().storage, pair_id, &oracle_holder)
+                .unwrap();
+        }
+
+        // a missing pair is an error, not a panic
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairDataInternal { pair_id: 1 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsData {
+                pair_ids: vec![61, 0],
+            },
+        )
+        .unwrap();
+        let value: PairsResponse = from_json(&res).unwrap();
+        let pairs: Vec<u32> = value.pairs.iter().map(|data| data.pair).collect();
+        assert_eq!(vec![61, 0], pairs);
+        assert_eq!(6100, value.pairs[0].price);
+
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsData {
+                pair_ids: vec![0, 1],
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPairsData {
+                pair_ids: (0..31).collect(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, StdError::GenericErr { .. }));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPairs {
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        let value: PairsResponse = from_json(&res).unwrap();
+        let pairs: Vec<u32> = value.pairs.iter().map(|data| data.pair).collect();
+        assert_eq!(vec![0, 21], pairs);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListPairs {
+                start_after: Some(21),
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: PairsResponse = from_json(&res).unwrap();
+        let pairs: Vec<u32> = value.pairs.iter().map(|data| data.pair).collect();
+        assert_eq!(vec![49, 61], pairs);
+    }
 }
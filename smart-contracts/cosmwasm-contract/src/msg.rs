@@ -5,10 +5,30 @@ pub struct InstantiateMsg {
     pub supra_pull_contract: String,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
-    UpdateSupraContract { supra_pull_contract: String },
-    VerifyOracleProof { proof_bytes: Vec<u8> },
+    /// Owner only
+    UpdateSupraContract {
+        supra_pull_contract: String,
+    },
+    VerifyOracleProof {
+        proof_bytes: Vec<u8>,
+    },
+    /// Owner only, first step of the ownership transfer
+    ProposeOwner {
+        new_owner: String,
+    },
+    /// Proposed owner only, second step of the ownership transfer
+    AcceptOwnership {},
+}
+
+/// Execute message of the Supra pull contract
+#[cw_serde]
+pub enum SupraPullExecuteMsg {
+    VerifyOracleProof { bytes_proof: Vec<u8> },
 }
 
 #[cw_serde]
@@ -18,10 +38,22 @@ pub enum QueryMsg {
     #[returns(ContractResponse)]
     GetSupraPullContract {},
     // Get price data from pair_id within own storage
-    #[returns(PriceData)]
+    #[returns(OracleHolder)]
     GetPairDataInternal { pair_id: u32 },
+    /// Get price data of several pairs within own storage, in the requested order, at most 30 pairs
+    #[returns(PairsResponse)]
+    GetPairsData { pair_ids: Vec<u32> },
+    /// List price data within own storage by ascending pair index
+    #[returns(PairsResponse)]
+    ListPairs {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(OracleHolder)]
     GetPairDataSupra { pair_id: u32 },
+    /// Get the owner and the owner proposed by an ongoing ownership transfer
+    #[returns(OwnerResponse)]
+    GetOwner {},
 }
 
 // We define a custom struct for each query response
@@ -30,6 +62,17 @@ pub struct ContractResponse {
     pub supra_pull_contract: String,
 }
 
+#[cw_serde]
+pub struct OwnerResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
+}
+
+#[cw_serde]
+pub struct PairsResponse {
+    pub pairs: Vec<PriceData>,
+}
+
 #[cw_serde]
 pub struct PriceData {
     pub pair: u32,
@@ -1,6 +1,6 @@
 use crate::msg::OracleHolder;
 use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,7 +9,20 @@ use std::collections::HashMap;
 pub struct State {
     pub supra_pull_contract: String,
     pub owner: Addr,
+}
+
+/// Layout of `STATE` before 0.2.0, when the price data was stored along with the config
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub supra_pull_contract: String,
+    pub owner: Addr,
+    #[serde(default)]
     pub price_data: HashMap<u32, OracleHolder>,
 }
 
 pub const STATE: Item<State> = Item::new("state");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+/// Owner proposed by the current owner, it becomes the owner once it accepts
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+/// Latest price data by pair index
+pub const PRICE_DATA: Map<u32, OracleHolder> = Map::new("price_data");